@@ -0,0 +1,602 @@
+use std::{collections::BTreeMap, error::Error, fmt, io, path::Path, str::FromStr};
+
+use crate::{Input, MouseButton, VirtualKeyCode};
+use crate::{ModifiersState, ShortcutTrigger};
+use crate::input::MAX_BUTTONS;
+
+/// An error produced when loading an `ActionMap` config.
+#[derive(Debug)]
+pub enum ActionMapError {
+    /// The file could not be read or written.
+    Io(io::Error),
+    /// A line of the config could not be parsed, `line` starts at 1.
+    Parse { line: usize, message: String }
+}
+
+impl fmt::Display for ActionMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionMapError::Io(e) => write!(f, "Failed to access action map config: {}", e),
+            ActionMapError::Parse { line, message } => write!(f, "Failed to parse action map config at line {}: {}", line, message)
+        }
+    }
+}
+
+impl Error for ActionMapError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ActionMapError::Io(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for ActionMapError {
+    fn from(e: io::Error) -> Self {
+        ActionMapError::Io(e)
+    }
+}
+
+/// A key or mouse button that triggers an action while `modifiers` are held.
+/// Other modifiers may be held as well, use `Input::shortcut_pressed` for exact modifier matching.
+///
+/// In a config a binding is written as its modifiers and trigger joined by `+`, e.g. `"Space"`, `"Ctrl+S"` or `"Shift+MouseLeft"`.
+/// Keys use their `VirtualKeyCode` name, mouse buttons are `MouseLeft`, `MouseRight`, `MouseMiddle` and `Mouse<N>` for other buttons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Binding {
+    pub trigger: ShortcutTrigger,
+    pub modifiers: ModifiersState
+}
+
+impl Binding {
+    /// Bind a key without modifiers.
+    pub fn key(key_code: VirtualKeyCode) -> Self {
+        Binding {
+            trigger: ShortcutTrigger::Key(key_code),
+            modifiers: ModifiersState::empty()
+        }
+    }
+
+    /// Bind a mouse button without modifiers.
+    pub fn mouse_button(button: MouseButton) -> Self {
+        Binding {
+            trigger: ShortcutTrigger::MouseButton(button),
+            modifiers: ModifiersState::empty()
+        }
+    }
+
+    /// Require modifiers to be held.
+    pub fn with_modifiers(mut self, modifiers: ModifiersState) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Get the binding of the first key or mouse button pressed this frame together with the held modifiers, for rebinding menus.
+    /// Modifier keys are returned on release so they can be part of a binding as well as bound on their own.
+    pub fn listen(input: &Input) -> Option<Self> {
+        let pressed_key = ALL_KEYS.iter()
+            .find(|&&key_code| !is_modifier_key(key_code) && input.key_down(key_code))
+            .map(|&key_code| Binding::key(key_code));
+        let pressed_button = || mouse_buttons()
+            .find(|&button| input.mouse_button_down(button))
+            .map(Binding::mouse_button);
+
+        if let Some(binding) = pressed_key.or_else(pressed_button) {
+            return Some(binding.with_modifiers(input.modifiers()));
+        }
+
+        ALL_KEYS.iter()
+            .find(|&&key_code| is_modifier_key(key_code) && input.key_up(key_code))
+            .map(|&key_code| Binding::key(key_code))
+    }
+
+    fn active(&self, input: &Input) -> bool {
+        input.modifiers().contains(self.modifiers)
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in MODIFIER_NAMES {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", TriggerName(self.trigger))
+    }
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let trigger = parse_trigger(parts.pop().unwrap_or_default())?;
+
+        let mut modifiers = ModifiersState::empty();
+        for part in parts {
+            let (modifier, _) = MODIFIER_NAMES.iter()
+                .find(|(_, name)| name.eq_ignore_ascii_case(part))
+                .ok_or_else(|| format!("Unknown modifier '{}'.", part))?;
+            modifiers |= *modifier;
+        }
+
+        Ok(Binding {
+            trigger,
+            modifiers
+        })
+    }
+}
+
+/// An input that drives an axis.
+/// - `Keys` is `-1` while `negative` is held, `1` while `positive` is held and `0` otherwise, written as `"A/D"` in a config.
+/// - `MouseX(f32)` and `MouseY(f32)` are the mouse delta multiplied by a scale, written as `"MouseX"` or `"MouseY*0.5"` in a config.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AxisBinding {
+    Keys { negative: ShortcutTrigger, positive: ShortcutTrigger },
+    MouseX(f32),
+    MouseY(f32)
+}
+
+impl fmt::Display for AxisBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, scale) = match self {
+            AxisBinding::Keys { negative, positive } => return write!(f, "{}/{}", TriggerName(*negative), TriggerName(*positive)),
+            AxisBinding::MouseX(scale) => ("MouseX", *scale),
+            AxisBinding::MouseY(scale) => ("MouseY", *scale)
+        };
+
+        if scale == 1.0 {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "{}*{}", name, scale)
+        }
+    }
+}
+
+impl FromStr for AxisBinding {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if let Some((negative, positive)) = text.split_once('/') {
+            return Ok(AxisBinding::Keys {
+                negative: parse_trigger(negative.trim())?,
+                positive: parse_trigger(positive.trim())?
+            });
+        }
+
+        let (name, scale) = match text.split_once('*') {
+            Some((name, scale)) => (name.trim(), scale.trim().parse().map_err(|_| format!("Invalid axis scale '{}'.", scale.trim()))?),
+            None => (text.trim(), 1.0)
+        };
+        match name {
+            "MouseX" => Ok(AxisBinding::MouseX(scale)),
+            "MouseY" => Ok(AxisBinding::MouseY(scale)),
+            _ => Err(format!("Unknown axis binding '{}'.", text))
+        }
+    }
+}
+
+/// Maps named actions and axes to rebindable inputs.
+/// Actions and axes are looked up by anything that implements `AsRef<str>`, like a `&str` or an enum of the game's actions.
+///
+/// Bindings can be stored in a line-based config that borrows TOML syntax, saved configs are valid TOML but only this subset is read back:
+/// - `[actions]` and `[axes]` section headers.
+/// - One `name = ["binding", ...]` entry per line with the whole list on that line and double-quoted bindings.
+/// - Names may be bare or double-quoted like `"Open menu" = ["Escape"]`.
+/// - Lines starting with `#` are comments. Comments after an entry, multi-line lists and single-quoted strings are errors.
+///
+/// ```text
+/// [actions]
+/// jump = ["Space", "MouseRight"]
+/// save = ["Ctrl+S"]
+///
+/// [axes]
+/// move_x = ["A/D", "Left/Right"]
+/// look_x = ["MouseX*0.5"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ActionMap {
+    actions: BTreeMap<String, Vec<Binding>>,
+    axes: BTreeMap<String, Vec<AxisBinding>>
+}
+
+impl ActionMap {
+    /// Create an action map without bindings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a binding to an action.
+    pub fn bind(&mut self, action: impl AsRef<str>, binding: Binding) {
+        self.actions.entry(action.as_ref().to_owned()).or_default().push(binding);
+    }
+
+    /// Replace all bindings of an action.
+    pub fn set_bindings(&mut self, action: impl AsRef<str>, bindings: Vec<Binding>) {
+        self.actions.insert(action.as_ref().to_owned(), bindings);
+    }
+
+    /// Get the bindings of an action.
+    pub fn bindings(&self, action: impl AsRef<str>) -> &[Binding] {
+        self.actions.get(action.as_ref()).map_or(&[], Vec::as_slice)
+    }
+
+    /// Add a binding to an axis.
+    pub fn bind_axis(&mut self, axis: impl AsRef<str>, binding: AxisBinding) {
+        self.axes.entry(axis.as_ref().to_owned()).or_default().push(binding);
+    }
+
+    /// Replace all bindings of an axis.
+    pub fn set_axis_bindings(&mut self, axis: impl AsRef<str>, bindings: Vec<AxisBinding>) {
+        self.axes.insert(axis.as_ref().to_owned(), bindings);
+    }
+
+    /// Get the bindings of an axis.
+    pub fn axis_bindings(&self, axis: impl AsRef<str>) -> &[AxisBinding] {
+        self.axes.get(axis.as_ref()).map_or(&[], Vec::as_slice)
+    }
+
+    /// Check if any binding of the action is pressed.
+    pub fn action(&self, input: &Input, action: impl AsRef<str>) -> bool {
+        self.bindings(action).iter().any(|binding| binding.active(input) && trigger(input, binding.trigger))
+    }
+
+    /// Check if any binding of the action is pressed AND was not pressed previous frame.
+    pub fn action_down(&self, input: &Input, action: impl AsRef<str>) -> bool {
+        self.bindings(action).iter().any(|binding| binding.active(input) && trigger_down(input, binding.trigger))
+    }
+
+    /// Check if any binding of the action is not pressed AND was pressed previous frame.
+    pub fn action_up(&self, input: &Input, action: impl AsRef<str>) -> bool {
+        self.bindings(action).iter().any(|binding| trigger_up(input, binding.trigger))
+    }
+
+    /// Get the value of an axis, key bindings add up to at most `[-1, 1]` and mouse bindings are added on top.
+    pub fn axis(&self, input: &Input, axis: impl AsRef<str>) -> f32 {
+        let mut keys = 0.0f32;
+        let mut mouse = 0.0;
+        for binding in self.axis_bindings(axis) {
+            match *binding {
+                AxisBinding::Keys { negative, positive } => {
+                    keys += trigger(input, positive) as i32 as f32 - trigger(input, negative) as i32 as f32;
+                },
+                AxisBinding::MouseX(scale) => mouse += input.mouse_delta().x * scale,
+                AxisBinding::MouseY(scale) => mouse += input.mouse_delta().y * scale
+            }
+        }
+        keys.clamp(-1.0, 1.0) + mouse
+    }
+
+    /// Parse bindings from a config, see `ActionMap`.
+    pub fn parse(config: &str) -> Result<Self, ActionMapError> {
+        enum Section { None, Actions, Axes }
+
+        let mut action_map = ActionMap::new();
+        let mut section = Section::None;
+        for (i, line) in config.lines().enumerate() {
+            let error = |message: String| ActionMapError::Parse { line: i + 1, message };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                section = match name.trim() {
+                    "actions" => Section::Actions,
+                    "axes" => Section::Axes,
+                    name => return Err(error(format!("Unknown section '{}'.", name)))
+                };
+                continue;
+            }
+
+            let (name, values) = parse_key(line).map_err(error)?;
+            let values = parse_list(values).map_err(error)?;
+            match section {
+                Section::Actions => {
+                    let bindings = values.iter().map(|value| value.parse()).collect::<Result<_, _>>().map_err(error)?;
+                    action_map.set_bindings(name, bindings);
+                },
+                Section::Axes => {
+                    let bindings = values.iter().map(|value| value.parse()).collect::<Result<_, _>>().map_err(error)?;
+                    action_map.set_axis_bindings(name, bindings);
+                },
+                Section::None => return Err(error("Expected [actions] or [axes] before bindings.".to_owned()))
+            }
+        }
+
+        Ok(action_map)
+    }
+
+    /// Write the bindings as a config, see `ActionMap`.
+    pub fn serialize(&self) -> String {
+        fn list<T: fmt::Display>(bindings: &[T]) -> String {
+            bindings.iter().map(|binding| format!("\"{}\"", binding)).collect::<Vec<_>>().join(", ")
+        }
+
+        let mut config = String::from("[actions]\n");
+        for (name, bindings) in &self.actions {
+            config += &format!("{} = [{}]\n", quote(name), list(bindings));
+        }
+        config += "\n[axes]\n";
+        for (name, bindings) in &self.axes {
+            config += &format!("{} = [{}]\n", quote(name), list(bindings));
+        }
+        config
+    }
+
+    /// Load bindings from a config file, see `ActionMap`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ActionMapError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Save the bindings to a config file, see `ActionMap`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ActionMapError> {
+        std::fs::write(path, self.serialize())?;
+        Ok(())
+    }
+}
+
+const MODIFIER_NAMES: [(ModifiersState, &str); 4] = [
+    (ModifiersState::CTRL, "Ctrl"),
+    (ModifiersState::SHIFT, "Shift"),
+    (ModifiersState::ALT, "Alt"),
+    (ModifiersState::LOGO, "Logo")
+];
+
+struct TriggerName(ShortcutTrigger);
+
+impl fmt::Display for TriggerName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ShortcutTrigger::Key(key_code) => write!(f, "{:?}", key_code),
+            ShortcutTrigger::MouseButton(MouseButton::Left) => write!(f, "MouseLeft"),
+            ShortcutTrigger::MouseButton(MouseButton::Right) => write!(f, "MouseRight"),
+            ShortcutTrigger::MouseButton(MouseButton::Middle) => write!(f, "MouseMiddle"),
+            ShortcutTrigger::MouseButton(MouseButton::Other(button)) => write!(f, "Mouse{}", button)
+        }
+    }
+}
+
+fn parse_trigger(text: &str) -> Result<ShortcutTrigger, String> {
+    let button = match text {
+        "MouseLeft" => Some(MouseButton::Left),
+        "MouseRight" => Some(MouseButton::Right),
+        "MouseMiddle" => Some(MouseButton::Middle),
+        _ => text.strip_prefix("Mouse").and_then(|button| button.parse().ok()).map(MouseButton::Other)
+    };
+    if let Some(button) = button {
+        // Input tracks a fixed number of buttons, Left, Right and Middle come first.
+        if let MouseButton::Other(index) = button {
+            if index as usize >= MAX_BUTTONS - 3 {
+                return Err(format!("Mouse button '{}' is out of range, the highest is Mouse{}.", text, MAX_BUTTONS - 4));
+            }
+        }
+        return Ok(ShortcutTrigger::MouseButton(button));
+    }
+
+    ALL_KEYS.iter()
+        .find(|key_code| format!("{:?}", key_code) == text)
+        .map(|&key_code| ShortcutTrigger::Key(key_code))
+        .ok_or_else(|| format!("Unknown key or mouse button '{}'.", text))
+}
+
+/// Parse a single line list of double-quoted strings.
+fn parse_list(text: &str) -> Result<Vec<String>, String> {
+    let inner = text.strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+        .ok_or_else(|| "Expected a list like [\"Space\", \"MouseLeft\"].".to_owned())?;
+
+    inner.split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| unquote(value).map(str::to_owned).ok_or_else(|| format!("Expected a quoted string, found {}.", value)))
+        .collect()
+}
+
+fn unquote(text: &str) -> Option<&str> {
+    text.strip_prefix('"').and_then(|text| text.strip_suffix('"'))
+}
+
+/// Split a `name = value` line, the name is either bare or a quoted string with `\"` and `\\` escapes.
+fn parse_key(line: &str) -> Result<(String, &str), String> {
+    let (name, rest) = match line.strip_prefix('"') {
+        Some(quoted) => {
+            let mut name = String::new();
+            let mut chars = quoted.char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, '"')) => break i + 1,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, character)) => name.push(character),
+                        None => return Err("Unterminated quoted name.".to_owned())
+                    },
+                    Some((_, character)) => name.push(character),
+                    None => return Err("Unterminated quoted name.".to_owned())
+                }
+            };
+            (name, &quoted[end..])
+        },
+        None => {
+            let name = line.split('=').next().unwrap_or_default();
+            (name.trim().to_owned(), &line[name.len()..])
+        }
+    };
+
+    let value = rest.trim_start().strip_prefix('=').ok_or_else(|| "Expected 'name = [...]'.".to_owned())?;
+    Ok((name, value.trim()))
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn trigger(input: &Input, trigger: ShortcutTrigger) -> bool {
+    match trigger {
+        ShortcutTrigger::Key(key_code) => input.key(key_code),
+        ShortcutTrigger::MouseButton(button) => input.mouse_button(button)
+    }
+}
+
+fn trigger_down(input: &Input, trigger: ShortcutTrigger) -> bool {
+    match trigger {
+        ShortcutTrigger::Key(key_code) => input.key_down(key_code),
+        ShortcutTrigger::MouseButton(button) => input.mouse_button_down(button)
+    }
+}
+
+fn trigger_up(input: &Input, trigger: ShortcutTrigger) -> bool {
+    match trigger {
+        ShortcutTrigger::Key(key_code) => input.key_up(key_code),
+        ShortcutTrigger::MouseButton(button) => input.mouse_button_up(button)
+    }
+}
+
+fn is_modifier_key(key_code: VirtualKeyCode) -> bool {
+    matches!(key_code,
+        VirtualKeyCode::LShift | VirtualKeyCode::RShift |
+        VirtualKeyCode::LControl | VirtualKeyCode::RControl |
+        VirtualKeyCode::LAlt | VirtualKeyCode::RAlt |
+        VirtualKeyCode::LWin | VirtualKeyCode::RWin
+    )
+}
+
+fn mouse_buttons() -> impl Iterator<Item = MouseButton> {
+    [MouseButton::Left, MouseButton::Right, MouseButton::Middle].into_iter()
+        .chain((0..(MAX_BUTTONS - 3) as u16).map(MouseButton::Other))
+}
+
+/// Every key, used to parse key names and to listen for the next key press.
+const ALL_KEYS: [VirtualKeyCode; 163] = [
+    VirtualKeyCode::Key1, VirtualKeyCode::Key2, VirtualKeyCode::Key3, VirtualKeyCode::Key4, VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6, VirtualKeyCode::Key7, VirtualKeyCode::Key8, VirtualKeyCode::Key9, VirtualKeyCode::Key0,
+    VirtualKeyCode::A, VirtualKeyCode::B, VirtualKeyCode::C, VirtualKeyCode::D, VirtualKeyCode::E, VirtualKeyCode::F,
+    VirtualKeyCode::G, VirtualKeyCode::H, VirtualKeyCode::I, VirtualKeyCode::J, VirtualKeyCode::K, VirtualKeyCode::L,
+    VirtualKeyCode::M, VirtualKeyCode::N, VirtualKeyCode::O, VirtualKeyCode::P, VirtualKeyCode::Q, VirtualKeyCode::R,
+    VirtualKeyCode::S, VirtualKeyCode::T, VirtualKeyCode::U, VirtualKeyCode::V, VirtualKeyCode::W, VirtualKeyCode::X,
+    VirtualKeyCode::Y, VirtualKeyCode::Z, VirtualKeyCode::Escape, VirtualKeyCode::F1, VirtualKeyCode::F2,
+    VirtualKeyCode::F3, VirtualKeyCode::F4, VirtualKeyCode::F5, VirtualKeyCode::F6, VirtualKeyCode::F7,
+    VirtualKeyCode::F8, VirtualKeyCode::F9, VirtualKeyCode::F10, VirtualKeyCode::F11, VirtualKeyCode::F12,
+    VirtualKeyCode::F13, VirtualKeyCode::F14, VirtualKeyCode::F15, VirtualKeyCode::F16, VirtualKeyCode::F17,
+    VirtualKeyCode::F18, VirtualKeyCode::F19, VirtualKeyCode::F20, VirtualKeyCode::F21, VirtualKeyCode::F22,
+    VirtualKeyCode::F23, VirtualKeyCode::F24, VirtualKeyCode::Snapshot, VirtualKeyCode::Scroll, VirtualKeyCode::Pause,
+    VirtualKeyCode::Insert, VirtualKeyCode::Home, VirtualKeyCode::Delete, VirtualKeyCode::End,
+    VirtualKeyCode::PageDown, VirtualKeyCode::PageUp, VirtualKeyCode::Left, VirtualKeyCode::Up, VirtualKeyCode::Right,
+    VirtualKeyCode::Down, VirtualKeyCode::Back, VirtualKeyCode::Return, VirtualKeyCode::Space, VirtualKeyCode::Compose,
+    VirtualKeyCode::Caret, VirtualKeyCode::Numlock, VirtualKeyCode::Numpad0, VirtualKeyCode::Numpad1,
+    VirtualKeyCode::Numpad2, VirtualKeyCode::Numpad3, VirtualKeyCode::Numpad4, VirtualKeyCode::Numpad5,
+    VirtualKeyCode::Numpad6, VirtualKeyCode::Numpad7, VirtualKeyCode::Numpad8, VirtualKeyCode::Numpad9,
+    VirtualKeyCode::NumpadAdd, VirtualKeyCode::NumpadDivide, VirtualKeyCode::NumpadDecimal,
+    VirtualKeyCode::NumpadComma, VirtualKeyCode::NumpadEnter, VirtualKeyCode::NumpadEquals,
+    VirtualKeyCode::NumpadMultiply, VirtualKeyCode::NumpadSubtract, VirtualKeyCode::AbntC1, VirtualKeyCode::AbntC2,
+    VirtualKeyCode::Apostrophe, VirtualKeyCode::Apps, VirtualKeyCode::Asterisk, VirtualKeyCode::At, VirtualKeyCode::Ax,
+    VirtualKeyCode::Backslash, VirtualKeyCode::Calculator, VirtualKeyCode::Capital, VirtualKeyCode::Colon,
+    VirtualKeyCode::Comma, VirtualKeyCode::Convert, VirtualKeyCode::Equals, VirtualKeyCode::Grave,
+    VirtualKeyCode::Kana, VirtualKeyCode::Kanji, VirtualKeyCode::LAlt, VirtualKeyCode::LBracket,
+    VirtualKeyCode::LControl, VirtualKeyCode::LShift, VirtualKeyCode::LWin, VirtualKeyCode::Mail,
+    VirtualKeyCode::MediaSelect, VirtualKeyCode::MediaStop, VirtualKeyCode::Minus, VirtualKeyCode::Mute,
+    VirtualKeyCode::MyComputer, VirtualKeyCode::NavigateForward, VirtualKeyCode::NavigateBackward,
+    VirtualKeyCode::NextTrack, VirtualKeyCode::NoConvert, VirtualKeyCode::OEM102, VirtualKeyCode::Period,
+    VirtualKeyCode::PlayPause, VirtualKeyCode::Plus, VirtualKeyCode::Power, VirtualKeyCode::PrevTrack,
+    VirtualKeyCode::RAlt, VirtualKeyCode::RBracket, VirtualKeyCode::RControl, VirtualKeyCode::RShift,
+    VirtualKeyCode::RWin, VirtualKeyCode::Semicolon, VirtualKeyCode::Slash, VirtualKeyCode::Sleep,
+    VirtualKeyCode::Stop, VirtualKeyCode::Sysrq, VirtualKeyCode::Tab, VirtualKeyCode::Underline,
+    VirtualKeyCode::Unlabeled, VirtualKeyCode::VolumeDown, VirtualKeyCode::VolumeUp, VirtualKeyCode::Wake,
+    VirtualKeyCode::WebBack, VirtualKeyCode::WebFavorites, VirtualKeyCode::WebForward, VirtualKeyCode::WebHome,
+    VirtualKeyCode::WebRefresh, VirtualKeyCode::WebSearch, VirtualKeyCode::WebStop, VirtualKeyCode::Yen,
+    VirtualKeyCode::Copy, VirtualKeyCode::Paste, VirtualKeyCode::Cut
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config = "
+            # Bindings
+            [actions]
+            jump = [\"Space\", \"MouseRight\"]
+            save = [\"Ctrl+Shift+S\"]
+            unbound = []
+
+            [axes]
+            move_x = [\"A/D\", \"Left/Right\"]
+            look_y = [\"MouseY*0.5\"]
+        ";
+        let action_map = ActionMap::parse(config).unwrap();
+
+        assert_eq!(action_map.bindings("jump"), &[Binding::key(VirtualKeyCode::Space), Binding::mouse_button(MouseButton::Right)]);
+        assert_eq!(action_map.bindings("save"), &[Binding::key(VirtualKeyCode::S).with_modifiers(ModifiersState::CTRL | ModifiersState::SHIFT)]);
+        assert!(action_map.bindings("unbound").is_empty());
+        assert!(action_map.bindings("missing").is_empty());
+        assert_eq!(action_map.axis_bindings("move_x"), &[
+            AxisBinding::Keys { negative: ShortcutTrigger::Key(VirtualKeyCode::A), positive: ShortcutTrigger::Key(VirtualKeyCode::D) },
+            AxisBinding::Keys { negative: ShortcutTrigger::Key(VirtualKeyCode::Left), positive: ShortcutTrigger::Key(VirtualKeyCode::Right) }
+        ]);
+        assert_eq!(action_map.axis_bindings("look_y"), &[AxisBinding::MouseY(0.5)]);
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let mut action_map = ActionMap::new();
+        action_map.bind("jump", Binding::key(VirtualKeyCode::Space));
+        action_map.bind("jump", Binding::mouse_button(MouseButton::Other(4)).with_modifiers(ModifiersState::ALT));
+        action_map.bind("a = b", Binding::key(VirtualKeyCode::Key1));
+        action_map.bind("# not a comment", Binding::mouse_button(MouseButton::Middle));
+        action_map.bind("[not a section]", Binding::key(VirtualKeyCode::Escape).with_modifiers(ModifiersState::LOGO));
+        action_map.bind("quote \" and \\ backslash", Binding::key(VirtualKeyCode::Comma));
+        action_map.set_bindings("unbound", Vec::new());
+        action_map.bind_axis("move_x", AxisBinding::Keys {
+            negative: ShortcutTrigger::Key(VirtualKeyCode::A),
+            positive: ShortcutTrigger::MouseButton(MouseButton::Left)
+        });
+        action_map.bind_axis("look_x", AxisBinding::MouseX(1.0));
+        action_map.bind_axis("look_y", AxisBinding::MouseY(-0.25));
+
+        let config = action_map.serialize();
+        assert_eq!(ActionMap::parse(&config).unwrap(), action_map);
+    }
+
+    #[test]
+    fn binding_names() {
+        for text in ["Space", "Ctrl+S", "Ctrl+Shift+Alt+Logo+F1", "Shift+MouseLeft", "MouseMiddle", "Mouse0", "Mouse28"] {
+            assert_eq!(text.parse::<Binding>().unwrap().to_string(), text);
+        }
+        assert_eq!("ctrl + S".parse::<Binding>().unwrap(), Binding::key(VirtualKeyCode::S).with_modifiers(ModifiersState::CTRL));
+        assert!("Hyper+S".parse::<Binding>().is_err());
+        assert!("NotAKey".parse::<Binding>().is_err());
+        assert!("".parse::<Binding>().is_err());
+    }
+
+    #[test]
+    fn mouse_button_out_of_range() {
+        assert!("Mouse29".parse::<Binding>().is_err());
+        assert!("Mouse100".parse::<Binding>().is_err());
+        assert!("Mouse100/MouseLeft".parse::<AxisBinding>().is_err());
+    }
+
+    #[test]
+    fn error_lines() {
+        let line = |config: &str| match ActionMap::parse(config) {
+            Err(ActionMapError::Parse { line, .. }) => line,
+            result => panic!("Expected a parse error, got {:?}.", result)
+        };
+
+        assert_eq!(line("jump = [\"Space\"]"), 1);
+        assert_eq!(line("[actions]\n\njump = [\"NotAKey\"]"), 3);
+        assert_eq!(line("[actions]\njump = [\"Mouse100\"]"), 2);
+        assert_eq!(line("[actions]\njump = \"Space\""), 2);
+        assert_eq!(line("[actions]\njump = [Space]"), 2);
+        assert_eq!(line("[actions]\njump"), 2);
+        assert_eq!(line("[actions]\n\"jump = [\"Space\"]"), 2);
+        assert_eq!(line("# comment\n[bindings]"), 2);
+        assert_eq!(line("[axes]\nmove_x = [\"MouseZ\"]"), 2);
+        assert_eq!(line("[axes]\nmove_x = [\"MouseX*fast\"]"), 2);
+
+        // TOML that is outside of the supported subset.
+        assert_eq!(line("[actions]\njump = [\"Space\"] # comment"), 2);
+        assert_eq!(line("[actions]\njump = [\n    \"Space\"\n]"), 2);
+        assert_eq!(line("[actions]\njump = ['Space']"), 2);
+    }
+}
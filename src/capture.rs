@@ -0,0 +1,96 @@
+use std::{io, path::{Path, PathBuf}};
+
+/// An 8-bit RGBA image of the window exactly as it was presented, including imgui and the cursor image.
+/// The color of a transparent window is premultiplied by its alpha, like the framebuffer it was read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capture {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32
+}
+
+impl Capture {
+    pub(crate) fn new(rgba: Vec<u8>, width: u32, height: u32) -> Self {
+        Capture {
+            rgba,
+            width,
+            height
+        }
+    }
+
+    /// Get the RGBA pixel data with the first row at the top.
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    /// Take the RGBA pixel data with the first row at the top.
+    pub fn into_rgba(self) -> Vec<u8> {
+        self.rgba
+    }
+
+    /// Get width.
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get height.
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Save the capture as a PNG file, the color is un-premultiplied since PNG stores straight alpha.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        image::save_buffer_with_format(path, &unpremultiply(&self.rgba), self.width, self.height, image::ExtendedColorType::Rgba8, image::ImageFormat::Png)
+            .map_err(|e| match e {
+                image::ImageError::IoError(e) => e,
+                e => io::Error::other(e)
+            })
+    }
+}
+
+/// Divide the color of premultiplied RGBA pixels by their alpha, fully transparent pixels become transparent black.
+fn unpremultiply(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|pixel| {
+            let alpha = pixel[3] as u32;
+            let channel = |value: u8| match alpha {
+                0 => 0,
+                _ => ((value as u32 * 255 + alpha / 2) / alpha).min(255) as u8
+            };
+            [channel(pixel[0]), channel(pixel[1]), channel(pixel[2]), pixel[3]]
+        })
+        .collect()
+}
+
+/// Where a requested capture ends up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum CaptureTarget {
+    /// Returned by `Window::take_capture`.
+    Memory,
+    /// Saved as a PNG file.
+    Path(PathBuf)
+}
+
+/// The file a capture hotkey press is saved to, in the working directory.
+pub(crate) fn hotkey_capture_path() -> PathBuf {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis());
+    PathBuf::from(format!("minigw_capture_{}.png", timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpremultiply_alpha() {
+        assert_eq!(unpremultiply(&[10, 20, 30, 255]), [10, 20, 30, 255]);
+        assert_eq!(unpremultiply(&[64, 32, 0, 128]), [128, 64, 0, 128]);
+        assert_eq!(unpremultiply(&[1, 1, 1, 3]), [85, 85, 85, 3]);
+        assert_eq!(unpremultiply(&[9, 9, 9, 0]), [0, 0, 0, 0]);
+        // Invalid premultiplied colors brighter than their alpha are clamped.
+        assert_eq!(unpremultiply(&[200, 0, 0, 100]), [255, 0, 0, 100]);
+        assert_eq!(unpremultiply(&[255, 0, 0, 255, 0, 0, 50, 100]), [255, 0, 0, 255, 0, 0, 128, 100]);
+    }
+}
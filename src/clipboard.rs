@@ -0,0 +1,66 @@
+pub use arboard::Error as ClipboardError;
+
+use std::borrow::Cow;
+
+use crate::RcCell;
+
+/// Wrapper around the system clipboard, shared between the window and imgui.
+pub(crate) struct Clipboard {
+    clipboard: Option<arboard::Clipboard>
+}
+
+impl Clipboard {
+    pub(crate) fn new() -> RcCell<Self> {
+        RcCell::new(Clipboard {
+            clipboard: None
+        })
+    }
+
+    /// The system clipboard is opened on first use, so a missing clipboard (e.g. headless) only fails clipboard calls.
+    fn internal_clipboard(&mut self) -> Result<&mut arboard::Clipboard, ClipboardError> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new()?);
+        }
+
+        Ok(self.clipboard.as_mut().unwrap())
+    }
+
+    pub(crate) fn get_text(&mut self) -> Result<String, ClipboardError> {
+        self.internal_clipboard()?.get_text()
+    }
+
+    pub(crate) fn set_text(&mut self, text: String) -> Result<(), ClipboardError> {
+        self.internal_clipboard()?.set_text(text)
+    }
+
+    pub(crate) fn set_image(&mut self, width: u32, height: u32, rgba: Vec<u8>) -> Result<(), ClipboardError> {
+        self.internal_clipboard()?.set_image(arboard::ImageData {
+            width: width as usize,
+            height: height as usize,
+            bytes: Cow::Owned(rgba)
+        })
+    }
+}
+
+/// Clipboard backend for imgui, makes Ctrl+C and Ctrl+V in text fields use the system clipboard.
+pub(crate) struct ImGuiClipboard {
+    clipboard: RcCell<Clipboard>
+}
+
+impl ImGuiClipboard {
+    pub(crate) fn new(clipboard: RcCell<Clipboard>) -> Self {
+        ImGuiClipboard {
+            clipboard
+        }
+    }
+}
+
+impl imgui::ClipboardBackend for ImGuiClipboard {
+    fn get(&mut self) -> Option<String> {
+        self.clipboard.as_mut().get_text().ok()
+    }
+
+    fn set(&mut self, value: &str) {
+        let _ = self.clipboard.as_mut().set_text(value.to_owned());
+    }
+}
\ No newline at end of file
@@ -1,4 +1,4 @@
-use glutin::event::{Event, VirtualKeyCode, ElementState, KeyboardInput, WindowEvent, DeviceEvent};
+use glutin::event::{Event, VirtualKeyCode, ElementState, KeyboardInput, WindowEvent, DeviceEvent, Ime};
 use glutin::event_loop::{ControlFlow, EventLoop};
 use cgmath::Vector2;
 
@@ -7,6 +7,7 @@ use crate::Window;
 use crate::Input;
 use crate::{Renderer, RenderTexture, RenderTextureType};
 use crate::gl_helpers::DebugUI;
+use crate::{CaptureTarget, hotkey_capture_path};
 
 pub struct CoreLoop {
     event_loop: EventLoop<()>
@@ -34,6 +35,11 @@ impl CoreLoop {
         F: FnMut(RcCell<Window>, RcCell<Input>, RcCell<RenderTexture<T>>, &mut DebugUI) + 'static
     {
         let mut renderer = Renderer::new(&rc_window.as_ref());
+        // Captures are encoded and written on their own threads, so a large PNG doesn't stall the frame.
+        let mut capture_saves: Vec<std::thread::JoinHandle<()>> = Vec::new();
+        if let Some(resizing) = rc_window.as_ref().restored_resizing() {
+            renderer.set_resizing_mode(resizing);
+        }
 
         self.event_loop.run(move |event, _, control_flow| {
             match event {
@@ -44,6 +50,19 @@ impl CoreLoop {
                         },
                         | WindowEvent::Resized(size) => {
                             renderer.resize(size.width, size.height);
+                            rc_window.as_mut().track_windowed_geometry();
+                        },
+                        | WindowEvent::Moved(_) => {
+                            rc_window.as_mut().track_windowed_geometry();
+                        },
+                        | WindowEvent::Focused(focused) => {
+                            rc_window.as_mut().set_focused(focused);
+                        },
+                        | WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+                            rc_window.as_mut().set_scale_factor_changed();
+                            rc_input.as_mut().set_scale_factor(scale_factor);
+                            renderer.set_scale_factor(scale_factor);
+                            renderer.resize(new_inner_size.width, new_inner_size.height);
                         },
                         | WindowEvent::KeyboardInput { input, .. } => {
                             match input {
@@ -53,18 +72,63 @@ impl CoreLoop {
                                             *control_flow = ControlFlow::Exit
                                         },
                                         | (Some(virtual_keycode), state) => {
-                                            rc_input.as_mut().set_key(virtual_keycode, state == ElementState::Pressed);
+                                            let pressed = state == ElementState::Pressed;
+                                            let repeat = pressed && rc_input.as_ref().key(virtual_keycode);
+                                            rc_input.as_mut().set_key(virtual_keycode, pressed, renderer.imgui());
+
+                                            if pressed && !repeat && rc_window.as_ref().get_capture_hotkey() == Some(virtual_keycode) {
+                                                rc_window.as_mut().capture_to_path(hotkey_capture_path());
+                                            }
+
+                                            if pressed && !repeat && rc_window.as_ref().get_fullscreen_hotkeys() {
+                                                let input = rc_input.as_ref();
+                                                let alt = input.modifiers().alt();
+                                                if virtual_keycode == VirtualKeyCode::F11 || (virtual_keycode == VirtualKeyCode::Return && alt) {
+                                                    rc_window.as_ref().toggle_fullscreen();
+                                                }
+                                            }
                                         },
                                         | _ => {}
                                     }
                                 },
                             }
                         },
+                        | WindowEvent::ModifiersChanged(modifiers) => {
+                            rc_input.as_mut().set_modifiers(modifiers, renderer.imgui());
+                        },
+                        | WindowEvent::ReceivedCharacter(character) => {
+                            rc_input.as_mut().add_character(character, renderer.imgui());
+                        },
+                        | WindowEvent::Ime(ime) => {
+                            match ime {
+                                | Ime::Preedit(preedit, cursor) => {
+                                    rc_input.as_mut().set_ime_preedit(preedit, cursor);
+                                },
+                                | Ime::Commit(commit) => {
+                                    rc_input.as_mut().add_ime_commit(&commit, renderer.imgui());
+                                },
+                                | Ime::Enabled | Ime::Disabled => {
+                                    rc_input.as_mut().set_ime_preedit(String::new(), None);
+                                }
+                            }
+                        },
                         | WindowEvent::MouseInput { state, button, .. } => {
                             rc_input.as_mut().set_mouse_button(button, state == ElementState::Pressed, renderer.imgui());
                         },
                         | WindowEvent::CursorMoved { position, .. } => {
                             rc_input.as_mut().set_mouse_pos(Vector2::new(position.x as i32, position.y as i32), renderer.imgui());
+                        },
+                        | WindowEvent::Touch(touch) => {
+                            rc_input.as_mut().add_touch(touch, renderer.imgui());
+                        },
+                        | WindowEvent::MouseWheel { delta, .. } => {
+                            rc_input.as_mut().add_scroll(delta, renderer.imgui());
+                        },
+                        | WindowEvent::CursorEntered { .. } => {
+                            rc_input.as_mut().set_mouse_inside(true);
+                        },
+                        | WindowEvent::CursorLeft { .. } => {
+                            rc_input.as_mut().set_mouse_inside(false);
                         }
                         | _ => {},
                     }
@@ -72,7 +136,19 @@ impl CoreLoop {
                 | Event::MainEventsCleared => {
                     rc_window.as_ref().internal_window().request_redraw();
                 },
+                | Event::Suspended => {
+                    renderer.suspend();
+                },
+                | Event::Resumed => {
+                    renderer.resume(&rc_window.as_ref());
+                },
                 | Event::RedrawRequested(_window_id) => {
+                    if !renderer.recover_context_loss(&mut rc_window.as_mut()) {
+                        return;
+                    }
+                    renderer.update_viewport(&rc_window.as_ref());
+                    rc_input.as_mut().poll_gamepads();
+
                     core_update(
                         rc_window.clone(),
                         rc_input.clone(),
@@ -81,11 +157,38 @@ impl CoreLoop {
                     );
 
                     rc_input.as_mut().update();
+                    rc_window.as_mut().update();
+
+                    if let Some(target) = rc_window.as_mut().take_capture_request() {
+                        renderer.request_capture(target);
+                    }
 
-                    renderer.render(&rc_window.as_ref());
+                    renderer.render(&rc_window.as_ref(), &rc_input.as_ref());
+
+                    if let Some(opaque) = renderer.take_cursor_opaque() {
+                        rc_window.as_mut().set_cursor_opaque(opaque);
+                    }
+
+                    match renderer.take_capture() {
+                        | Some((capture, CaptureTarget::Memory)) => {
+                            rc_window.as_mut().set_capture(capture);
+                        },
+                        | Some((capture, CaptureTarget::Path(path))) => {
+                            capture_saves.retain(|save| !save.is_finished());
+                            capture_saves.push(std::thread::spawn(move || {
+                                if let Err(e) = capture.save_png(&path) {
+                                    eprintln!("Failed to save capture to {:?}: {}", path, e);
+                                }
+                            }));
+                        },
+                        | None => {}
+                    }
                 },
                 | Event::LoopDestroyed => {
-                    
+                    rc_window.as_ref().save_state(renderer.render_texture().as_ref().get_resizing_mode());
+                    for save in capture_saves.drain(..) {
+                        let _ = save.join();
+                    }
                 },
                 | Event::DeviceEvent { event, ..} => {
                     match event {
@@ -0,0 +1,131 @@
+use std::{error::Error, fmt, path::Path};
+
+use cgmath::Vector2;
+
+use crate::decode::{decode_rgba, ImageLoadError};
+
+/// An error produced when using `CursorImage::from_rgba` with invalid arguments.
+#[derive(Debug)]
+pub enum BadCursorImage {
+    /// The length of the `rgba` argument isn't divisible by 4.
+    ByteCountNotDivisibleBy4 { byte_count: usize },
+    /// The number of pixels (`rgba.len() / 4`) isn't equal to `width * height`.
+    DimensionsVsPixelCount { width: u32, height: u32, pixel_count: usize },
+    /// The hotspot lies outside of the image.
+    HotspotOutOfBounds { width: u32, height: u32, hotspot: Vector2<u32> }
+}
+
+impl fmt::Display for BadCursorImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BadCursorImage::ByteCountNotDivisibleBy4 { byte_count } => write!(f,
+                "The length of the `rgba` argument ({}) isn't divisible by 4.",
+                byte_count
+            ),
+            BadCursorImage::DimensionsVsPixelCount { width, height, pixel_count } => write!(f,
+                "The specified dimensions ({}x{}) don't match the number of pixels supplied by the `rgba` argument ({}).",
+                width, height, pixel_count
+            ),
+            BadCursorImage::HotspotOutOfBounds { width, height, hotspot } => write!(f,
+                "The hotspot ({}, {}) lies outside of the {}x{} image.",
+                hotspot.x, hotspot.y, width, height
+            )
+        }
+    }
+}
+
+impl Error for BadCursorImage {}
+
+/// A cursor built from RGBA pixel data.
+/// The hotspot is the pixel of the image that lies exactly at the mouse position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CursorImage {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+    hotspot: Vector2<u32>
+}
+
+impl CursorImage {
+    /// Create a cursor image from 8-bit RGBA pixel data with the first row at the top.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32, hotspot: Vector2<u32>) -> Result<Self, BadCursorImage> {
+        if !rgba.len().is_multiple_of(4) {
+            return Err(BadCursorImage::ByteCountNotDivisibleBy4 { byte_count: rgba.len() });
+        }
+
+        let pixel_count = rgba.len() / 4;
+        if width.checked_mul(height).is_none_or(|size| size as usize != pixel_count) {
+            return Err(BadCursorImage::DimensionsVsPixelCount { width, height, pixel_count });
+        }
+
+        if hotspot.x >= width || hotspot.y >= height {
+            return Err(BadCursorImage::HotspotOutOfBounds { width, height, hotspot });
+        }
+
+        Ok(CursorImage {
+            rgba,
+            width,
+            height,
+            hotspot
+        })
+    }
+
+    /// Decode a cursor image from PNG, ICO or BMP data, for a multi-resolution ICO the largest image is used.
+    /// Returns `ImageLoadError::BadCursorImage` if the hotspot lies outside of the decoded image.
+    pub fn from_bytes(bytes: &[u8], hotspot: Vector2<u32>) -> Result<Self, ImageLoadError> {
+        let image = decode_rgba(bytes, u32::MAX)?;
+        Self::from_rgba(image.rgba, image.width, image.height, hotspot)
+            .map_err(ImageLoadError::BadCursorImage)
+    }
+
+    /// Decode a cursor image from a PNG, ICO or BMP file, for a multi-resolution ICO the largest image is used.
+    pub fn from_path(path: impl AsRef<Path>, hotspot: Vector2<u32>) -> Result<Self, ImageLoadError> {
+        Self::from_bytes(&std::fs::read(path)?, hotspot)
+    }
+
+    /// Get the RGBA pixel data.
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    /// Get width.
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get height.
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the hotspot.
+    pub fn get_hotspot(&self) -> Vector2<u32> {
+        self.hotspot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_rgba() {
+        let cursor = CursorImage::from_rgba(vec![255; 2 * 3 * 4], 2, 3, Vector2::new(1, 2)).unwrap();
+        assert_eq!((cursor.get_width(), cursor.get_height(), cursor.get_hotspot()), (2, 3, Vector2::new(1, 2)));
+
+        assert!(matches!(CursorImage::from_rgba(vec![0; 7], 1, 2, Vector2::new(0, 0)), Err(BadCursorImage::ByteCountNotDivisibleBy4 { byte_count: 7 })));
+        assert!(matches!(CursorImage::from_rgba(vec![0; 8], 3, 1, Vector2::new(0, 0)), Err(BadCursorImage::DimensionsVsPixelCount { .. })));
+        assert!(matches!(CursorImage::from_rgba(vec![0; 8], 2, 1, Vector2::new(0, 1)), Err(BadCursorImage::HotspotOutOfBounds { .. })));
+        assert!(matches!(CursorImage::from_rgba(vec![], 0, 0, Vector2::new(0, 0)), Err(BadCursorImage::HotspotOutOfBounds { .. })));
+    }
+
+    #[test]
+    fn dimensions_overflow() {
+        let result = CursorImage::from_rgba(vec![0; 4], u32::MAX, u32::MAX, Vector2::new(0, 0));
+        assert!(matches!(result, Err(BadCursorImage::DimensionsVsPixelCount { width: u32::MAX, height: u32::MAX, pixel_count: 1 })));
+
+        // 65536 * 65536 wraps to 0 in u32.
+        let result = CursorImage::from_rgba(vec![], 1 << 16, 1 << 16, Vector2::new(0, 0));
+        assert!(matches!(result, Err(BadCursorImage::DimensionsVsPixelCount { .. })));
+    }
+}
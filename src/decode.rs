@@ -0,0 +1,210 @@
+use std::{error::Error, fmt, io, path::Path};
+
+use crate::{Icon, BadIcon, BadCursorImage};
+
+/// An error produced when decoding an icon or cursor image.
+#[derive(Debug)]
+pub enum ImageLoadError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The data is not a PNG, ICO or BMP image.
+    UnsupportedFormat,
+    /// The data is a supported format but could not be decoded.
+    Decode(String),
+    /// The decoded pixels were rejected when creating the icon.
+    BadIcon(BadIcon),
+    /// The decoded pixels or hotspot were rejected when creating the cursor image.
+    BadCursorImage(BadCursorImage)
+}
+
+impl fmt::Display for ImageLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageLoadError::Io(e) => write!(f, "Failed to read image file: {}", e),
+            ImageLoadError::UnsupportedFormat => write!(f, "Unsupported image format, expected PNG, ICO or BMP."),
+            ImageLoadError::Decode(e) => write!(f, "Failed to decode image: {}", e),
+            ImageLoadError::BadIcon(e) => write!(f, "Failed to create icon: {}", e),
+            ImageLoadError::BadCursorImage(e) => write!(f, "Failed to create cursor image: {}", e)
+        }
+    }
+}
+
+impl Error for ImageLoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImageLoadError::Io(e) => Some(e),
+            ImageLoadError::BadIcon(e) => Some(e),
+            ImageLoadError::BadCursorImage(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for ImageLoadError {
+    fn from(e: io::Error) -> Self {
+        ImageLoadError::Io(e)
+    }
+}
+
+/// Constructors that decode PNG, ICO and BMP data into an `Icon`.
+pub trait IconExt: Sized {
+    /// Decode an icon from PNG, ICO or BMP data, for a multi-resolution ICO the largest image is used.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ImageLoadError>;
+
+    /// Decode an icon from PNG, ICO or BMP data, for a multi-resolution ICO the image closest to `size` pixels is used.
+    fn from_bytes_sized(bytes: &[u8], size: u32) -> Result<Self, ImageLoadError>;
+
+    /// Decode an icon from a PNG, ICO or BMP file, for a multi-resolution ICO the largest image is used.
+    fn from_path(path: impl AsRef<Path>) -> Result<Self, ImageLoadError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+impl IconExt for Icon {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ImageLoadError> {
+        Self::from_bytes_sized(bytes, u32::MAX)
+    }
+
+    fn from_bytes_sized(bytes: &[u8], size: u32) -> Result<Self, ImageLoadError> {
+        let image = decode_rgba(bytes, size)?;
+        Icon::from_rgba(image.rgba, image.width, image.height)
+            .map_err(ImageLoadError::BadIcon)
+    }
+}
+
+pub(crate) struct DecodedImage {
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32
+}
+
+/// Decode PNG, ICO or BMP data to 8-bit RGBA, for a multi-resolution ICO the image closest to `size` is used.
+pub(crate) fn decode_rgba(bytes: &[u8], size: u32) -> Result<DecodedImage, ImageLoadError> {
+    let format = image::guess_format(bytes).map_err(|_| ImageLoadError::UnsupportedFormat)?;
+
+    let image = match format {
+        image::ImageFormat::Png | image::ImageFormat::Bmp => image::load_from_memory_with_format(bytes, format),
+        image::ImageFormat::Ico => image::load_from_memory_with_format(&ico_entry(bytes, size)?, format),
+        _ => return Err(ImageLoadError::UnsupportedFormat)
+    }.map_err(|e| ImageLoadError::Decode(e.to_string()))?;
+
+    let image = image.into_rgba8();
+    Ok(DecodedImage {
+        width: image.width(),
+        height: image.height(),
+        rgba: image.into_raw()
+    })
+}
+
+const ICO_HEADER_SIZE: usize = 6;
+const ICO_ENTRY_SIZE: usize = 16;
+
+/// Build a single image ICO file from the entry of a multi-resolution ICO that fits `size` best.
+/// The smallest entry that is at least `size` pixels wide is preferred, otherwise the largest entry is used.
+fn ico_entry(bytes: &[u8], size: u32) -> Result<Vec<u8>, ImageLoadError> {
+    let invalid = || ImageLoadError::Decode("Invalid ICO directory.".to_owned());
+
+    let count = u16::from_le_bytes([*bytes.get(4).ok_or_else(invalid)?, *bytes.get(5).ok_or_else(invalid)?]) as usize;
+    let entries = bytes.get(ICO_HEADER_SIZE..ICO_HEADER_SIZE + count * ICO_ENTRY_SIZE).ok_or_else(invalid)?;
+
+    // A width or height of 0 means 256 pixels.
+    let dim = |dim: u8| if dim == 0 { 256 } else { dim as u32 };
+    let entry_size = |entry: &[u8]| std::cmp::max(dim(entry[0]), dim(entry[1]));
+    let entry_bits = |entry: &[u8]| u16::from_le_bytes([entry[6], entry[7]]);
+
+    let entry = entries.chunks_exact(ICO_ENTRY_SIZE)
+        .max_by_key(|entry| {
+            let entry_size = entry_size(entry);
+            // Fitting entries rank above too small ones, the closer to `size` the better.
+            let rank = if entry_size >= size { u32::MAX - entry_size } else { entry_size };
+            (entry_size >= size, rank, entry_bits(entry))
+        })
+        .ok_or_else(invalid)?;
+
+    let length = u32::from_le_bytes([entry[8], entry[9], entry[10], entry[11]]) as usize;
+    let offset = u32::from_le_bytes([entry[12], entry[13], entry[14], entry[15]]) as usize;
+    let data = offset.checked_add(length).and_then(|end| bytes.get(offset..end)).ok_or_else(invalid)?;
+
+    let mut ico = Vec::with_capacity(ICO_HEADER_SIZE + ICO_ENTRY_SIZE + data.len());
+    ico.extend_from_slice(&[0, 0, 1, 0, 1, 0]);
+    ico.extend_from_slice(&entry[..12]);
+    ico.extend_from_slice(&((ICO_HEADER_SIZE + ICO_ENTRY_SIZE) as u32).to_le_bytes());
+    ico.extend_from_slice(data);
+    Ok(ico)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build an ICO directory with one entry per (size, bits), the data of entry `i` is four bytes of value `i`.
+    fn ico(entries: &[(u8, u16)]) -> Vec<u8> {
+        let mut ico = vec![0, 0, 1, 0];
+        ico.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        let data_start = ICO_HEADER_SIZE + entries.len() * ICO_ENTRY_SIZE;
+        for (i, &(size, bits)) in entries.iter().enumerate() {
+            ico.extend_from_slice(&[size, size, 0, 0, 1, 0]);
+            ico.extend_from_slice(&bits.to_le_bytes());
+            ico.extend_from_slice(&4u32.to_le_bytes());
+            ico.extend_from_slice(&((data_start + i * 4) as u32).to_le_bytes());
+        }
+        for i in 0..entries.len() {
+            ico.extend_from_slice(&[i as u8; 4]);
+        }
+        ico
+    }
+
+    /// The index of the entry `ico_entry` picked, read back from its data.
+    fn picked(bytes: &[u8], size: u32) -> u8 {
+        let entry = ico_entry(bytes, size).unwrap();
+        assert_eq!(&entry[..6], &[0, 0, 1, 0, 1, 0]);
+        assert_eq!(u32::from_le_bytes([entry[18], entry[19], entry[20], entry[21]]), 22);
+        assert_eq!(entry.len(), 26);
+        entry[22]
+    }
+
+    #[test]
+    fn size_selection() {
+        // 0 is 256 pixels.
+        let bytes = ico(&[(16, 32), (32, 32), (48, 32), (0, 32)]);
+        assert_eq!(picked(&bytes, 16), 0);
+        assert_eq!(picked(&bytes, 20), 1);
+        assert_eq!(picked(&bytes, 32), 1);
+        assert_eq!(picked(&bytes, 40), 2);
+        assert_eq!(picked(&bytes, 256), 3);
+        assert_eq!(picked(&bytes, 300), 3);
+        assert_eq!(picked(&bytes, u32::MAX), 3);
+        assert_eq!(picked(&bytes, 0), 0);
+
+        // Equal sizes prefer more bits per pixel.
+        let bytes = ico(&[(32, 8), (32, 32), (32, 4)]);
+        assert_eq!(picked(&bytes, 32), 1);
+    }
+
+    #[test]
+    fn truncated_header() {
+        assert!(matches!(ico_entry(&[], 32), Err(ImageLoadError::Decode(_))));
+        assert!(matches!(ico_entry(&[0, 0, 1, 0, 1], 32), Err(ImageLoadError::Decode(_))));
+        assert!(matches!(ico_entry(&ico(&[]), 32), Err(ImageLoadError::Decode(_))));
+
+        // The directory claims more entries than there are bytes.
+        let mut bytes = ico(&[(32, 32)]);
+        bytes[4] = 3;
+        assert!(matches!(ico_entry(&bytes, 32), Err(ImageLoadError::Decode(_))));
+    }
+
+    #[test]
+    fn bad_entry_offset() {
+        let mut bytes = ico(&[(32, 32)]);
+        bytes[18..22].copy_from_slice(&100u32.to_le_bytes());
+        assert!(matches!(ico_entry(&bytes, 32), Err(ImageLoadError::Decode(_))));
+
+        bytes[18..22].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(ico_entry(&bytes, 32), Err(ImageLoadError::Decode(_))));
+
+        // The data length runs past the end.
+        let mut bytes = ico(&[(32, 32)]);
+        bytes[14..18].copy_from_slice(&5u32.to_le_bytes());
+        assert!(matches!(ico_entry(&bytes, 32), Err(ImageLoadError::Decode(_))));
+    }
+}
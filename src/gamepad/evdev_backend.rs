@@ -0,0 +1,220 @@
+use std::{collections::HashSet, io, os::unix::io::AsRawFd, path::{Path, PathBuf}, time::{Duration, Instant}};
+
+use evdev::{AbsoluteAxisType, Device, InputEventKind, Key};
+
+use super::{GamepadAxis, GamepadBackend, GamepadButton, GamepadEvent};
+
+/// How often `/dev/input` is scanned for newly plugged in gamepads.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+struct EvdevGamepad {
+    device_id: u32,
+    path: PathBuf,
+    device: Device,
+    /// Minimum and maximum of every absolute axis, indexed by axis code.
+    ranges: Vec<(i32, i32)>
+}
+
+/// Reads gamepads from the Linux evdev interface, devices need to be readable by the user (usually the `input` group).
+pub(crate) struct EvdevBackend {
+    gamepads: Vec<EvdevGamepad>,
+    known_paths: HashSet<PathBuf>,
+    /// Stable identity of every gamepad seen so far, indexed by device id so a replugged gamepad gets its old id back.
+    identities: Vec<String>,
+    last_scan: Option<Instant>
+}
+
+impl EvdevBackend {
+    pub(crate) fn new() -> Self {
+        EvdevBackend {
+            gamepads: Vec::new(),
+            known_paths: HashSet::new(),
+            identities: Vec::new(),
+            last_scan: None
+        }
+    }
+
+    fn scan(&mut self, events: &mut Vec<(u32, GamepadEvent)>) {
+        let entries = match std::fs::read_dir("/dev/input") {
+            Ok(entries) => entries,
+            Err(_) => return
+        };
+        let paths: HashSet<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("event")))
+            .collect();
+
+        // Only open devices that appeared since the previous scan, a reused event node is opened again.
+        let new_paths: Vec<PathBuf> = paths.difference(&self.known_paths).cloned().collect();
+        for path in &new_paths {
+            if let Some(device) = open_gamepad(path) {
+                let gamepad = self.identify(path.clone(), device);
+                let name = gamepad.device.name().unwrap_or("Gamepad").to_owned();
+                events.push((gamepad.device_id, GamepadEvent::Connected { name }));
+                initial_axes(&gamepad, events);
+                self.gamepads.push(gamepad);
+            }
+        }
+        self.known_paths = paths;
+    }
+}
+
+impl EvdevBackend {
+    /// Assign the device id of a previously seen gamepad with the same identity that is not open, or a new one.
+    fn identify(&mut self, path: PathBuf, (device, ranges): (Device, Vec<(i32, i32)>)) -> EvdevGamepad {
+        // The unique id is usually the serial or bluetooth address, the physical path the USB port.
+        let identity = match device.unique_name().filter(|unique_name| !unique_name.is_empty()) {
+            Some(unique_name) => format!("uniq:{}", unique_name),
+            None => format!("phys:{}:{}", device.physical_path().unwrap_or_default(), device.name().unwrap_or_default())
+        };
+
+        let open = |device_id: u32| self.gamepads.iter().any(|gamepad| gamepad.device_id == device_id);
+        let device_id = match (0..self.identities.len() as u32).find(|&device_id| self.identities[device_id as usize] == identity && !open(device_id)) {
+            Some(device_id) => device_id,
+            None => {
+                self.identities.push(identity);
+                self.identities.len() as u32 - 1
+            }
+        };
+
+        EvdevGamepad {
+            device_id,
+            path,
+            device,
+            ranges
+        }
+    }
+}
+
+impl GamepadBackend for EvdevBackend {
+    fn poll(&mut self, events: &mut Vec<(u32, GamepadEvent)>) {
+        if self.last_scan.is_none_or(|last_scan| last_scan.elapsed() >= RESCAN_INTERVAL) {
+            self.last_scan = Some(Instant::now());
+            self.scan(events);
+        }
+
+        let known_paths = &mut self.known_paths;
+        self.gamepads.retain_mut(|gamepad| {
+            let device_id = gamepad.device_id;
+            let ranges = &gamepad.ranges;
+            match gamepad.device.fetch_events() {
+                Ok(device_events) => {
+                    for event in device_events {
+                        match event.kind() {
+                            InputEventKind::Key(key) => {
+                                if let Some(button) = evdev_to_button(key) {
+                                    events.push((device_id, GamepadEvent::Button(button, event.value() != 0)));
+                                }
+                            },
+                            InputEventKind::AbsAxis(axis) => {
+                                abs_event(device_id, axis, event.value(), ranges, events);
+                            },
+                            _ => {}
+                        }
+                    }
+                    true
+                },
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => true,
+                Err(_) => {
+                    events.push((device_id, GamepadEvent::Disconnected));
+                    // Open the node again when it is reused before the next scan notices it was removed.
+                    known_paths.remove(&gamepad.path);
+                    false
+                }
+            }
+        });
+    }
+}
+
+fn open_gamepad(path: &Path) -> Option<(Device, Vec<(i32, i32)>)> {
+    let device = Device::open(path).ok()?;
+    if !device.supported_keys().is_some_and(|keys| keys.contains(Key::BTN_SOUTH)) {
+        return None;
+    }
+
+    // Events are polled once per frame, reads must not block the event loop.
+    let fd = device.as_raw_fd();
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            return None;
+        }
+    }
+
+    let ranges = device.get_abs_state()
+        .map(|state| state.iter().map(|info| (info.minimum, info.maximum)).collect())
+        .unwrap_or_default();
+
+    Some((device, ranges))
+}
+
+/// Report the resting position of every axis, events only arrive once an axis moves.
+fn initial_axes(gamepad: &EvdevGamepad, events: &mut Vec<(u32, GamepadEvent)>) {
+    let state = match gamepad.device.get_abs_state() {
+        Ok(state) => state,
+        Err(_) => return
+    };
+    let axes = match gamepad.device.supported_absolute_axes() {
+        Some(axes) => axes,
+        None => return
+    };
+
+    for axis in axes.iter() {
+        abs_event(gamepad.device_id, axis, state[axis.0 as usize].value, &gamepad.ranges, events);
+    }
+}
+
+fn abs_event(device_id: u32, axis: AbsoluteAxisType, value: i32, ranges: &[(i32, i32)], events: &mut Vec<(u32, GamepadEvent)>) {
+    // The d-pad of many controllers is reported as a hat axis of -1, 0 or 1.
+    let hat = |negative, positive| [
+        (device_id, GamepadEvent::Button(negative, value < 0)),
+        (device_id, GamepadEvent::Button(positive, value > 0))
+    ];
+    match axis {
+        AbsoluteAxisType::ABS_HAT0X => return events.extend(hat(GamepadButton::DPadLeft, GamepadButton::DPadRight)),
+        AbsoluteAxisType::ABS_HAT0Y => return events.extend(hat(GamepadButton::DPadUp, GamepadButton::DPadDown)),
+        _ => {}
+    }
+
+    let (minimum, maximum) = ranges.get(axis.0 as usize).copied().unwrap_or((0, 0));
+    let (axis, stick, flip) = match axis {
+        AbsoluteAxisType::ABS_X => (GamepadAxis::LeftStickX, true, false),
+        AbsoluteAxisType::ABS_Y => (GamepadAxis::LeftStickY, true, true),
+        AbsoluteAxisType::ABS_RX => (GamepadAxis::RightStickX, true, false),
+        AbsoluteAxisType::ABS_RY => (GamepadAxis::RightStickY, true, true),
+        AbsoluteAxisType::ABS_Z => (GamepadAxis::LeftTrigger, false, false),
+        AbsoluteAxisType::ABS_RZ => (GamepadAxis::RightTrigger, false, false),
+        _ => return
+    };
+
+    if maximum <= minimum {
+        return;
+    }
+    let t = (value - minimum) as f32 / (maximum - minimum) as f32;
+    let value = if stick { t * 2.0 - 1.0 } else { t };
+    // evdev sticks point down for positive Y.
+    events.push((device_id, GamepadEvent::Axis(axis, if flip { -value } else { value })));
+}
+
+fn evdev_to_button(key: Key) -> Option<GamepadButton> {
+    match key {
+        Key::BTN_SOUTH => Some(GamepadButton::South),
+        Key::BTN_EAST => Some(GamepadButton::East),
+        Key::BTN_NORTH => Some(GamepadButton::North),
+        Key::BTN_WEST => Some(GamepadButton::West),
+        Key::BTN_TL => Some(GamepadButton::LeftBumper),
+        Key::BTN_TR => Some(GamepadButton::RightBumper),
+        Key::BTN_TL2 => Some(GamepadButton::LeftTrigger),
+        Key::BTN_TR2 => Some(GamepadButton::RightTrigger),
+        Key::BTN_SELECT => Some(GamepadButton::Select),
+        Key::BTN_START => Some(GamepadButton::Start),
+        Key::BTN_MODE => Some(GamepadButton::Mode),
+        Key::BTN_THUMBL => Some(GamepadButton::LeftThumb),
+        Key::BTN_THUMBR => Some(GamepadButton::RightThumb),
+        Key::BTN_DPAD_UP => Some(GamepadButton::DPadUp),
+        Key::BTN_DPAD_DOWN => Some(GamepadButton::DPadDown),
+        Key::BTN_DPAD_LEFT => Some(GamepadButton::DPadLeft),
+        Key::BTN_DPAD_RIGHT => Some(GamepadButton::DPadRight),
+        _ => None
+    }
+}
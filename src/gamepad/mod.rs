@@ -0,0 +1,463 @@
+use std::collections::VecDeque;
+
+use crate::RcCell;
+
+#[cfg(target_os = "linux")]
+mod evdev_backend;
+#[cfg(target_os = "linux")]
+pub(crate) use evdev_backend::EvdevBackend;
+
+/// A gamepad button, named after its position on the controller.
+/// `South` is A on Xbox and Cross on PlayStation controllers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight
+}
+
+const BUTTON_COUNT: usize = GamepadButton::DPadRight as usize + 1;
+
+/// A gamepad axis.
+/// Sticks range from `-1.0` to `1.0` with positive values pointing right and up, triggers range from `0.0` to `1.0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger
+}
+
+const AXIS_COUNT: usize = GamepadAxis::RightTrigger as usize + 1;
+
+/// Identifies a gamepad for as long as the application runs, a gamepad keeps its id when it reconnects.
+/// The evdev backend recognizes a replugged gamepad by its unique id, or by its USB port and name if it has none.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GamepadId(usize);
+
+/// A gamepad connection change, see `Input::gamepad_connection_events`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamepadConnection {
+    Connected,
+    Disconnected
+}
+
+/// An event reported by a `GamepadBackend` for one of its devices.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GamepadEvent {
+    Connected { name: String },
+    Disconnected,
+    Button(GamepadButton, bool),
+    /// An axis value in the range of `GamepadAxis`, before the deadzone is applied.
+    Axis(GamepadAxis, f32)
+}
+
+/// A source of gamepad events, see `Input::add_gamepad_backend`.
+/// On Linux an evdev backend is added by default.
+pub trait GamepadBackend {
+    /// Push the events that happened since the previous poll as `(device, event)`, `device` identifies a gamepad within this backend.
+    fn poll(&mut self, events: &mut Vec<(u32, GamepadEvent)>);
+}
+
+/// A software gamepad that can be driven by code, e.g. to test controller input without hardware.
+/// Changes are applied at the start of the next frame, see `Input::connect_virtual_gamepad`.
+#[derive(Clone)]
+pub struct VirtualGamepad {
+    name: String,
+    events: RcCell<VecDeque<GamepadEvent>>
+}
+
+impl VirtualGamepad {
+    /// Press or release a button.
+    pub fn set_button(&self, button: GamepadButton, pressed: bool) {
+        self.events.as_mut().push_back(GamepadEvent::Button(button, pressed));
+    }
+
+    /// Move an axis, the value is clamped to the range of the axis.
+    pub fn set_axis(&self, axis: GamepadAxis, value: f32) {
+        self.events.as_mut().push_back(GamepadEvent::Axis(axis, clamp_axis(axis, value)));
+    }
+
+    /// Reconnect the gamepad after it was disconnected, it keeps its `GamepadId`.
+    pub fn connect(&self) {
+        self.events.as_mut().push_back(GamepadEvent::Connected { name: self.name.clone() });
+    }
+
+    /// Disconnect the gamepad, all buttons are released.
+    pub fn disconnect(&self) {
+        self.events.as_mut().push_back(GamepadEvent::Disconnected);
+    }
+}
+
+impl GamepadBackend for VirtualGamepad {
+    fn poll(&mut self, events: &mut Vec<(u32, GamepadEvent)>) {
+        events.extend(self.events.as_mut().drain(..).map(|event| (0, event)));
+    }
+}
+
+struct GamepadState {
+    backend: usize,
+    device: u32,
+    name: String,
+    connected: bool,
+    buttons: [bool; BUTTON_COUNT],
+    buttons_prev: [bool; BUTTON_COUNT],
+    axes: [f32; AXIS_COUNT]
+}
+
+/// The state of every gamepad that connected since startup, `Input` owns one and forwards to it.
+/// It can also be used on its own without a window, e.g. to test controller handling with a `VirtualGamepad`:
+/// ```rust
+/// use minigw::{Gamepads, GamepadButton};
+///
+/// let mut gamepads = Gamepads::without_backends();
+/// let pad = gamepads.connect_virtual("Test pad");
+/// gamepads.poll();
+/// let id = gamepads.ids().next().unwrap();
+///
+/// pad.set_button(GamepadButton::South, true);
+/// gamepads.poll();
+/// assert!(gamepads.button_down(id, GamepadButton::South));
+/// gamepads.update();
+/// ```
+pub struct Gamepads {
+    backends: Vec<Box<dyn GamepadBackend>>,
+    gamepads: Vec<GamepadState>,
+    connection_events: Vec<(GamepadId, GamepadConnection)>,
+    deadzone: f32
+}
+
+impl Gamepads {
+    /// Create the gamepad state with the platform backend, evdev on Linux.
+    pub fn new() -> Self {
+        let mut gamepads = Self::without_backends();
+        #[cfg(target_os = "linux")]
+        gamepads.add_backend(Box::new(EvdevBackend::new()));
+        gamepads
+    }
+
+    /// Create the gamepad state without any backend, only gamepads added with `add_backend` or `connect_virtual` show up.
+    pub fn without_backends() -> Self {
+        Gamepads {
+            backends: Vec::new(),
+            gamepads: Vec::new(),
+            connection_events: Vec::new(),
+            deadzone: 0.1
+        }
+    }
+
+    /// Apply the events of every backend, call at the start of a frame.
+    pub fn poll(&mut self) {
+        let mut events = Vec::new();
+        let mut backend_events = Vec::new();
+        for (backend, gamepad_backend) in self.backends.iter_mut().enumerate() {
+            gamepad_backend.poll(&mut backend_events);
+            events.extend(backend_events.drain(..).map(|(device, event)| (backend, device, event)));
+        }
+
+        for (backend, device, event) in events {
+            self.handle_event(backend, device, event);
+        }
+    }
+
+    /// Finish a frame, `button_down`, `button_up` and `connection_events` are relative to the previous call.
+    pub fn update(&mut self) {
+        for gamepad in &mut self.gamepads {
+            gamepad.buttons_prev = gamepad.buttons;
+        }
+        self.connection_events.clear();
+    }
+
+    /// Add a source of gamepad events.
+    pub fn add_backend(&mut self, backend: Box<dyn GamepadBackend>) {
+        self.backends.push(backend);
+    }
+
+    /// Connect a software gamepad that can be driven through the returned handle, it shows up after the next `poll`.
+    pub fn connect_virtual(&mut self, name: impl Into<String>) -> VirtualGamepad {
+        let gamepad = VirtualGamepad {
+            name: name.into(),
+            events: RcCell::new(VecDeque::new())
+        };
+        gamepad.connect();
+        self.add_backend(Box::new(gamepad.clone()));
+        gamepad
+    }
+
+    /// Get the gamepads that are currently connected.
+    pub fn ids(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.gamepads.iter().enumerate()
+            .filter(|(_, gamepad)| gamepad.connected)
+            .map(|(id, _)| GamepadId(id))
+    }
+
+    /// Get the gamepads that connected or disconnected this frame.
+    pub fn connection_events(&self) -> &[(GamepadId, GamepadConnection)] {
+        &self.connection_events
+    }
+
+    /// Check if gamepad is connected.
+    pub fn connected(&self, id: GamepadId) -> bool {
+        self.gamepads.get(id.0).is_some_and(|gamepad| gamepad.connected)
+    }
+
+    /// Get the name the gamepad reported when it connected.
+    pub fn name(&self, id: GamepadId) -> Option<&str> {
+        self.gamepads.get(id.0).map(|gamepad| gamepad.name.as_str())
+    }
+
+    /// Check if gamepad button is pressed.
+    pub fn button(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads.get(id.0).is_some_and(|gamepad| gamepad.buttons[button as usize])
+    }
+
+    /// Check if gamepad button is pressed AND was not pressed previous frame.
+    pub fn button_down(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads.get(id.0).is_some_and(|gamepad| gamepad.buttons[button as usize] && !gamepad.buttons_prev[button as usize])
+    }
+
+    /// Check if gamepad button is not pressed AND was pressed previous frame.
+    pub fn button_up(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads.get(id.0).is_some_and(|gamepad| !gamepad.buttons[button as usize] && gamepad.buttons_prev[button as usize])
+    }
+
+    /// Get the value of a gamepad axis with the deadzone applied, zero if the gamepad is not connected.
+    pub fn axis(&self, id: GamepadId, axis: GamepadAxis) -> f32 {
+        let value = self.gamepads.get(id.0).map_or(0.0, |gamepad| gamepad.axes[axis as usize]);
+        apply_deadzone(value, self.deadzone)
+    }
+
+    /// Get the deadzone.
+    pub fn get_deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
+    /// Set the deadzone, axis values closer to zero read as zero and the rest is rescaled to start at zero. Defaults to `0.1`.
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        self.deadzone = deadzone.clamp(0.0, 0.99);
+    }
+
+    fn handle_event(&mut self, backend: usize, device: u32, event: GamepadEvent) {
+        let index = self.gamepads.iter().position(|gamepad| gamepad.backend == backend && gamepad.device == device);
+
+        match (index, event) {
+            (index, GamepadEvent::Connected { name }) => {
+                let index = index.unwrap_or_else(|| {
+                    self.gamepads.push(GamepadState {
+                        backend,
+                        device,
+                        name: String::new(),
+                        connected: false,
+                        buttons: [false; BUTTON_COUNT],
+                        buttons_prev: [false; BUTTON_COUNT],
+                        axes: [0.0; AXIS_COUNT]
+                    });
+                    self.gamepads.len() - 1
+                });
+
+                let gamepad = &mut self.gamepads[index];
+                gamepad.name = name;
+                if !gamepad.connected {
+                    gamepad.connected = true;
+                    self.connection_events.push((GamepadId(index), GamepadConnection::Connected));
+                }
+            },
+            (Some(index), GamepadEvent::Disconnected) => {
+                let gamepad = &mut self.gamepads[index];
+                if gamepad.connected {
+                    gamepad.connected = false;
+                    gamepad.buttons = [false; BUTTON_COUNT];
+                    gamepad.axes = [0.0; AXIS_COUNT];
+                    self.connection_events.push((GamepadId(index), GamepadConnection::Disconnected));
+                }
+            },
+            (Some(index), GamepadEvent::Button(button, pressed)) => {
+                let gamepad = &mut self.gamepads[index];
+                if gamepad.connected {
+                    gamepad.buttons[button as usize] = pressed;
+                }
+            },
+            (Some(index), GamepadEvent::Axis(axis, value)) => {
+                let gamepad = &mut self.gamepads[index];
+                if gamepad.connected {
+                    gamepad.axes[axis as usize] = clamp_axis(axis, value);
+                }
+            },
+            // Events for devices that never connected.
+            (None, _) => {}
+        }
+    }
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn clamp_axis(axis: GamepadAxis, value: f32) -> f32 {
+    match axis {
+        GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => value.clamp(0.0, 1.0),
+        _ => value.clamp(-1.0, 1.0)
+    }
+}
+
+/// Zero values inside the deadzone and rescale the rest so the output still starts at zero and reaches one.
+fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    if value.abs() <= deadzone {
+        0.0
+    } else {
+        value.signum() * (value.abs() - deadzone) / (1.0 - deadzone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connect(gamepads: &mut Gamepads) -> (VirtualGamepad, GamepadId) {
+        let pad = gamepads.connect_virtual("Test pad");
+        gamepads.poll();
+        let id = gamepads.ids().last().unwrap();
+        (pad, id)
+    }
+
+    #[test]
+    fn connect_and_disconnect() {
+        let mut gamepads = Gamepads::without_backends();
+        let pad = gamepads.connect_virtual("Test pad");
+        assert_eq!(gamepads.ids().count(), 0);
+
+        gamepads.poll();
+        let id = gamepads.ids().next().unwrap();
+        assert!(gamepads.connected(id));
+        assert_eq!(gamepads.name(id), Some("Test pad"));
+        assert_eq!(gamepads.connection_events(), &[(id, GamepadConnection::Connected)]);
+
+        gamepads.update();
+        assert!(gamepads.connection_events().is_empty());
+
+        pad.set_button(GamepadButton::Start, true);
+        pad.set_axis(GamepadAxis::LeftStickX, 1.0);
+        pad.disconnect();
+        gamepads.poll();
+        assert!(!gamepads.connected(id));
+        assert_eq!(gamepads.ids().count(), 0);
+        assert_eq!(gamepads.connection_events(), &[(id, GamepadConnection::Disconnected)]);
+        assert!(!gamepads.button(id, GamepadButton::Start));
+        assert_eq!(gamepads.axis(id, GamepadAxis::LeftStickX), 0.0);
+
+        gamepads.update();
+        pad.connect();
+        gamepads.poll();
+        assert_eq!(gamepads.ids().collect::<Vec<_>>(), vec![id]);
+        assert_eq!(gamepads.connection_events(), &[(id, GamepadConnection::Connected)]);
+    }
+
+    #[test]
+    fn separate_gamepads() {
+        let mut gamepads = Gamepads::without_backends();
+        let (first, first_id) = connect(&mut gamepads);
+        let (_, second_id) = connect(&mut gamepads);
+        assert_ne!(first_id, second_id);
+
+        first.set_button(GamepadButton::South, true);
+        gamepads.poll();
+        assert!(gamepads.button(first_id, GamepadButton::South));
+        assert!(!gamepads.button(second_id, GamepadButton::South));
+    }
+
+    #[test]
+    fn button_edges() {
+        let mut gamepads = Gamepads::without_backends();
+        let (pad, id) = connect(&mut gamepads);
+        gamepads.update();
+
+        pad.set_button(GamepadButton::South, true);
+        assert!(!gamepads.button(id, GamepadButton::South));
+        gamepads.poll();
+        assert!(gamepads.button(id, GamepadButton::South));
+        assert!(gamepads.button_down(id, GamepadButton::South));
+        assert!(!gamepads.button_up(id, GamepadButton::South));
+        gamepads.update();
+
+        gamepads.poll();
+        assert!(gamepads.button(id, GamepadButton::South));
+        assert!(!gamepads.button_down(id, GamepadButton::South));
+        gamepads.update();
+
+        pad.set_button(GamepadButton::South, false);
+        gamepads.poll();
+        assert!(!gamepads.button(id, GamepadButton::South));
+        assert!(gamepads.button_up(id, GamepadButton::South));
+        gamepads.update();
+
+        gamepads.poll();
+        assert!(!gamepads.button_up(id, GamepadButton::South));
+    }
+
+    #[test]
+    fn disconnect_releases_buttons() {
+        let mut gamepads = Gamepads::without_backends();
+        let (pad, id) = connect(&mut gamepads);
+        pad.set_button(GamepadButton::East, true);
+        gamepads.poll();
+        gamepads.update();
+
+        pad.disconnect();
+        gamepads.poll();
+        assert!(gamepads.button_up(id, GamepadButton::East));
+    }
+
+    #[test]
+    fn deadzone() {
+        let mut gamepads = Gamepads::without_backends();
+        let (pad, id) = connect(&mut gamepads);
+        let axis = |gamepads: &mut Gamepads, axis, value| {
+            pad.set_axis(axis, value);
+            gamepads.poll();
+            gamepads.axis(id, axis)
+        };
+
+        assert_eq!(gamepads.get_deadzone(), 0.1);
+        assert_eq!(axis(&mut gamepads, GamepadAxis::LeftStickX, 0.05), 0.0);
+        assert_eq!(axis(&mut gamepads, GamepadAxis::LeftStickX, -0.1), 0.0);
+        assert!((axis(&mut gamepads, GamepadAxis::LeftStickX, 0.55) - 0.5).abs() < 1e-6);
+        assert!((axis(&mut gamepads, GamepadAxis::LeftStickY, -0.55) + 0.5).abs() < 1e-6);
+        assert_eq!(axis(&mut gamepads, GamepadAxis::RightStickX, 1.0), 1.0);
+        assert_eq!(axis(&mut gamepads, GamepadAxis::RightStickX, -2.0), -1.0);
+        assert_eq!(axis(&mut gamepads, GamepadAxis::LeftTrigger, -0.5), 0.0);
+        assert_eq!(axis(&mut gamepads, GamepadAxis::RightTrigger, 1.5), 1.0);
+
+        gamepads.set_deadzone(0.0);
+        assert_eq!(axis(&mut gamepads, GamepadAxis::LeftStickX, 0.05), 0.05);
+        gamepads.set_deadzone(2.0);
+        assert_eq!(gamepads.get_deadzone(), 0.99);
+    }
+
+    #[test]
+    fn unknown_gamepad() {
+        let gamepads = Gamepads::without_backends();
+        let id = GamepadId(3);
+        assert!(!gamepads.connected(id));
+        assert!(!gamepads.button(id, GamepadButton::South));
+        assert_eq!(gamepads.axis(id, GamepadAxis::LeftStickX), 0.0);
+        assert_eq!(gamepads.name(id), None);
+    }
+}
@@ -0,0 +1,127 @@
+/// The OpenGL API and profile of a context.
+/// - `Any` desktop OpenGL with the profile left up to the driver.
+/// - `Core` desktop OpenGL core profile.
+/// - `Compatibility` desktop OpenGL compatibility profile.
+/// - `Gles` OpenGL ES.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GlProfile {
+    Any,
+    Core,
+    Compatibility,
+    Gles
+}
+
+/// OpenGL context creation settings, see `WindowConfig::gl`.
+/// If a context with these settings can't be created minigw falls back to OpenGL 2.0 or OpenGL ES 3.0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlConfig {
+    /// Requested (major, minor) version, defaults to `(3, 0)`.
+    pub version: (u8, u8),
+    /// Requested API and profile, defaults to `GlProfile::Any`.
+    pub profile: GlProfile,
+    /// Request a debug context, GL debug messages are printed to stderr. Defaults to `false`.
+    pub debug: bool,
+    /// Request an sRGB-capable default framebuffer and use it to gamma correct the framebuffer, ignored if the context can't toggle sRGB writes. Defaults to `false`.
+    pub srgb: bool,
+    /// Number of multisampling samples, must be 0 or a power of two. Defaults to `0`.
+    pub samples: u16,
+    /// Number of depth buffer bits, defaults to `24`.
+    pub depth_bits: u8,
+    /// Number of stencil buffer bits, defaults to `8`.
+    pub stencil_bits: u8
+}
+
+impl Default for GlConfig {
+    fn default() -> Self {
+        GlConfig {
+            version: (3, 0),
+            profile: GlProfile::Any,
+            debug: false,
+            srgb: false,
+            samples: 0,
+            depth_bits: 24,
+            stencil_bits: 8
+        }
+    }
+}
+
+impl GlConfig {
+    pub(crate) fn gl_request(&self) -> glutin::GlRequest {
+        match self.profile {
+            GlProfile::Gles => glutin::GlRequest::Specific(glutin::Api::OpenGlEs, self.version),
+            _ => glutin::GlRequest::Specific(glutin::Api::OpenGl, self.version)
+        }
+    }
+
+    pub(crate) fn gl_profile(&self) -> Option<glutin::GlProfile> {
+        match self.profile {
+            GlProfile::Core => Some(glutin::GlProfile::Core),
+            GlProfile::Compatibility => Some(glutin::GlProfile::Compatibility),
+            _ => None
+        }
+    }
+}
+
+/// The OpenGL context that was actually created, see `Window::gl_info()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlInfo {
+    /// (major, minor) version.
+    pub version: (u8, u8),
+    /// API and profile, `GlProfile::Any` if the driver doesn't report it.
+    pub profile: GlProfile,
+    /// If the context is a debug context.
+    pub debug: bool,
+    /// If the default framebuffer is sRGB-capable.
+    pub srgb: bool,
+    /// Number of multisampling samples, 0 if multisampling is disabled.
+    pub samples: u16,
+    /// Number of depth buffer bits.
+    pub depth_bits: u8,
+    /// Number of stencil buffer bits.
+    pub stencil_bits: u8,
+    /// If the context is hardware accelerated.
+    pub hardware_accelerated: bool,
+    /// The `GL_VERSION` string.
+    pub version_string: String,
+    /// The `GL_SHADING_LANGUAGE_VERSION` string.
+    pub shading_language_version: String,
+    /// The `GL_RENDERER` string.
+    pub renderer: String,
+    /// The `GL_VENDOR` string.
+    pub vendor: String
+}
+
+/// Parse the first `major.minor` pair of a `GL_VERSION` string, e.g. "OpenGL ES 3.2 Mesa 23.0" or "4.6 (Core Profile) Mesa 23.0".
+pub(crate) fn parse_gl_version(version: &str) -> (u8, u8) {
+    version.split(|c: char| !c.is_ascii_digit() && c != '.')
+        .filter_map(|word| {
+            let mut numbers = word.split('.');
+            Some((numbers.next()?.parse().ok()?, numbers.next()?.parse().ok()?))
+        })
+        .next()
+        .unwrap_or((0, 0))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn driver_version_strings() {
+        assert_eq!(parse_gl_version("4.6.0 NVIDIA 535.54.03"), (4, 6));
+        assert_eq!(parse_gl_version("4.6 (Core Profile) Mesa 23.0.4"), (4, 6));
+        assert_eq!(parse_gl_version("OpenGL ES 3.2 Mesa 23.0.4"), (3, 2));
+        assert_eq!(parse_gl_version("OpenGL ES 3.0 build 1.13@5776728"), (3, 0));
+        assert_eq!(parse_gl_version("3.3.0 - Build 31.0.101.2111"), (3, 3));
+        assert_eq!(parse_gl_version("2.1"), (2, 1));
+    }
+
+    #[test]
+    fn garbage_version_strings() {
+        assert_eq!(parse_gl_version(""), (0, 0));
+        assert_eq!(parse_gl_version("not a version"), (0, 0));
+        assert_eq!(parse_gl_version("OpenGL ES 3"), (0, 0));
+        assert_eq!(parse_gl_version("...."), (0, 0));
+        // Numbers that don't fit are skipped.
+        assert_eq!(parse_gl_version("999.1 2.0"), (2, 0));
+    }
+}
@@ -36,6 +36,7 @@ pub struct GLRBO {
 #[allow(clippy::upper_case_acronyms)]
 pub struct GLPBO {
     buffer: GLBuffer,
+    target: GLenum,
     size: usize
 }
 
@@ -57,6 +58,13 @@ pub fn gl_enable_vertex_attrib_array(index: u32) {
     }
 }
 
+pub fn gl_disable_vertex_attrib_array(index: u32) {
+    unsafe {
+        gl::DisableVertexAttribArray(index);
+        gl_check();
+    }
+}
+
 pub fn gl_frame_buffer_texture_2d(texture: &GLTexture, attachment: GLenum) {
     unsafe {
         gl::FramebufferTexture2D(gl::FRAMEBUFFER, attachment, gl::TEXTURE_2D, texture.handle(), 0);
@@ -284,20 +292,21 @@ impl IGLBuffer for GLPBO {
     fn new() -> Self {
         GLPBO {
             buffer: gl_gen_buffer(),
+            target: gl::PIXEL_UNPACK_BUFFER,
             size: 0
         }
     }
 
     fn bind(&self) {
         unsafe {
-            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, self.buffer);
+            gl::BindBuffer(self.target, self.buffer);
             gl_check();
         }
     }
 
     fn unbind(&self) {
         unsafe {
-            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+            gl::BindBuffer(self.target, 0);
             gl_check();
         }
     }
@@ -308,31 +317,46 @@ impl IGLBuffer for GLPBO {
 }
 
 impl GLPBO {
+    /// Create a PBO that pixels are read back into with `glReadPixels`.
+    pub fn new_pack() -> Self {
+        GLPBO {
+            buffer: gl_gen_buffer(),
+            target: gl::PIXEL_PACK_BUFFER,
+            size: 0
+        }
+    }
+
     pub fn handle(&self) -> GLBuffer {
         self.buffer
     }
 
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
     pub fn allocate(&mut self, size: usize) {
         self.size = size;
 
+        let usage = if self.target == gl::PIXEL_PACK_BUFFER { gl::STREAM_READ } else { gl::STREAM_DRAW };
         unsafe {
             gl::BufferData(
-                gl::PIXEL_UNPACK_BUFFER,
+                self.target,
                 size as isize,
                 std::ptr::null(),
-                gl::STREAM_DRAW
+                usage
             );
             gl_check();
         }
     }
 
     pub fn map<T>(&self) -> *mut T {
+        let access = if self.target == gl::PIXEL_PACK_BUFFER { gl::MAP_READ_BIT } else { gl::MAP_WRITE_BIT };
         unsafe {
             let data_ptr = gl::MapBufferRange(
-                gl::PIXEL_UNPACK_BUFFER,
+                self.target,
                 0,
                 self.size as isize,
-                gl::MAP_WRITE_BIT
+                access
             ) as *mut T;
             gl_check();
             data_ptr
@@ -341,7 +365,7 @@ impl GLPBO {
 
     pub fn unmap(&self) {
         unsafe {
-            gl::UnmapBuffer(gl::PIXEL_UNPACK_BUFFER);
+            gl::UnmapBuffer(self.target);
             gl_check();
         }
     }
@@ -0,0 +1,114 @@
+use super::*;
+
+/*****************************************************************************
+*                               STRUCTS
+******************************************************************************/
+
+/// The GLSL dialect shaders are compiled with, picked to match the context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GLSLVersion {
+    /// GLSL ES 1.00, OpenGL ES 2.x.
+    Glsl100Es,
+    /// GLSL ES 3.00, OpenGL ES 3.x.
+    Glsl300Es,
+    /// GLSL 1.10, OpenGL 2.x.
+    Glsl110,
+    /// GLSL 1.30, OpenGL 3.0 and 3.1.
+    Glsl130,
+    /// GLSL 1.50, OpenGL 3.2 (the lowest version macOS core profiles accept).
+    Glsl150,
+    /// GLSL 3.30, OpenGL 3.3 and up.
+    Glsl330
+}
+
+/// Features the renderers can rely on in the current context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GLCapabilities {
+    pub glsl_version: GLSLVersion,
+    pub gles: bool,
+    /// Vertex array objects, GL 3.0 / GLES 3.0.
+    pub vao: bool,
+    /// Pixel buffer objects with `glMapBufferRange`, GL 3.0 / GLES 3.0.
+    pub pbo: bool,
+    /// Sampler objects, GL 3.3 / GLES 3.0.
+    pub samplers: bool,
+    /// Toggling `GL_FRAMEBUFFER_SRGB`, GL 3.0, `ARB_framebuffer_sRGB` or `EXT_sRGB_write_control` on GLES.
+    pub framebuffer_srgb: bool
+}
+
+/*****************************************************************************
+*                               IMPLEMENTATION
+******************************************************************************/
+
+impl GLCapabilities {
+    pub fn new(info: &GlInfo) -> Self {
+        let gles = info.profile == GlProfile::Gles;
+        let glsl_version = match (gles, info.version) {
+            (true, version) if version >= (3, 0) => GLSLVersion::Glsl300Es,
+            (true, _) => GLSLVersion::Glsl100Es,
+            (false, version) if version >= (3, 3) => GLSLVersion::Glsl330,
+            (false, (3, 2)) => GLSLVersion::Glsl150,
+            (false, version) if version >= (3, 0) => GLSLVersion::Glsl130,
+            (false, _) => GLSLVersion::Glsl110
+        };
+
+        let gl3 = info.version >= (3, 0);
+        GLCapabilities {
+            glsl_version,
+            gles,
+            vao: gl3 && gl::GenVertexArrays::is_loaded(),
+            pbo: gl3 && gl::MapBufferRange::is_loaded(),
+            samplers: ((gles && gl3) || info.version >= (3, 3)) && gl::BindSampler::is_loaded(),
+            framebuffer_srgb: if gles {
+                gl_has_extension(info.version, "GL_EXT_sRGB_write_control")
+            } else {
+                gl3 || gl_has_extension(info.version, "GL_ARB_framebuffer_sRGB") || gl_has_extension(info.version, "GL_EXT_framebuffer_sRGB")
+            }
+        }
+    }
+}
+
+impl GLSLVersion {
+    /// Prefix a vertex shader body with the version directive and the compatibility macros.
+    /// Bodies use `VS_IN`, `VS_OUT` for attributes and varyings.
+    pub fn vertex_source(&self, body: &str) -> String {
+        let macros = if self.legacy() {
+            "#define VS_IN attribute\n#define VS_OUT varying\n"
+        } else {
+            "#define VS_IN in\n#define VS_OUT out\n"
+        };
+
+        format!("{}{}{}", self.directive(), macros, body)
+    }
+
+    /// Prefix a fragment shader body with the version directive, a default precision and the compatibility macros.
+    /// Bodies use `FS_IN` for varyings, `TEXTURE` to sample a `sampler2D` and write their result to `FRAG_COLOR`.
+    pub fn fragment_source(&self, body: &str) -> String {
+        let precision = match self {
+            GLSLVersion::Glsl100Es | GLSLVersion::Glsl300Es => "precision mediump float;\n",
+            _ => ""
+        };
+        let macros = if self.legacy() {
+            "#define FS_IN varying\n#define TEXTURE texture2D\n#define FRAG_COLOR gl_FragColor\n"
+        } else {
+            "#define FS_IN in\n#define TEXTURE texture\nout vec4 FragColor;\n#define FRAG_COLOR FragColor\n"
+        };
+
+        format!("{}{}{}{}", self.directive(), precision, macros, body)
+    }
+
+    fn directive(&self) -> &'static str {
+        match self {
+            GLSLVersion::Glsl100Es => "#version 100\n",
+            GLSLVersion::Glsl300Es => "#version 300 es\n",
+            GLSLVersion::Glsl110 => "#version 110\n",
+            GLSLVersion::Glsl130 => "#version 130\n",
+            GLSLVersion::Glsl150 => "#version 150 core\n",
+            GLSLVersion::Glsl330 => "#version 330 core\n"
+        }
+    }
+
+    fn legacy(&self) -> bool {
+        matches!(self, GLSLVersion::Glsl100Es | GLSLVersion::Glsl110)
+    }
+}
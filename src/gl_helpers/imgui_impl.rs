@@ -1,36 +1,92 @@
 use super::*;
 use std::mem;
+use std::cell::Cell;
+
+use crate::RcCell;
+use crate::{Clipboard, ImGuiClipboard};
 
 pub type DebugUI = imgui::Ui;
 
+/// Position of the text cursor of the focused imgui text field, in logical pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImGuiImeData {
+    pub visible: bool,
+    pub position: cgmath::Vector2<f32>,
+    pub line_height: f32
+}
+
+thread_local! {
+    // Imgui reports IME data through a callback without user data, it is only called when the data changes.
+    static IME_DATA: Cell<Option<ImGuiImeData>> = const { Cell::new(None) };
+}
+
+unsafe extern "C" fn set_platform_ime_data(_viewport: *mut imgui::sys::ImGuiViewport, data: *mut imgui::sys::ImGuiPlatformImeData) {
+    let data = &*data;
+    IME_DATA.with(|ime_data| ime_data.set(Some(ImGuiImeData {
+        visible: data.WantVisible,
+        position: cgmath::Vector2::new(data.InputPos.x, data.InputPos.y),
+        line_height: data.InputLineHeight
+    })));
+}
+
 pub struct ImGui {
     context: imgui::Context,
     renderer: Renderer
 }
 
 impl ImGui {
-    pub fn new() -> Self {
+    pub fn new(clipboard: RcCell<Clipboard>, capabilities: GLCapabilities) -> Self {
         let mut context = imgui::Context::create();
-        let renderer = Renderer::new(&mut context);
+        context.set_clipboard_backend(ImGuiClipboard::new(clipboard));
+        context.io_mut().set_platform_ime_data_fn = Some(set_platform_ime_data);
+        let renderer = Renderer::new(&mut context, capabilities);
         ImGui {
             context,
             renderer
         }
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        self.context.io_mut().display_size[0] = width as f32;
-        self.context.io_mut().display_size[1] = height as f32;
+    pub fn resize(&mut self, width: u32, height: u32, scale_factor: f64) {
+        let scale_factor = scale_factor as f32;
+        self.context.io_mut().display_size[0] = width as f32 / scale_factor;
+        self.context.io_mut().display_size[1] = height as f32 / scale_factor;
+        self.context.io_mut().display_framebuffer_scale = [scale_factor, scale_factor];
     }
 
     pub fn mouse_button_event(&mut self, button: imgui::MouseButton, down: bool) {
         self.context.io_mut().add_mouse_button_event(button, down);
     }
 
+    pub fn key_event(&mut self, key: imgui::Key, down: bool) {
+        self.context.io_mut().add_key_event(key, down);
+    }
+
+    pub fn character_event(&mut self, character: char) {
+        if character != '\u{7f}' {
+            self.context.io_mut().add_input_character(character);
+        }
+    }
+
+    /// Take the IME data imgui reported since the previous call, `None` if it did not change.
+    pub fn take_ime_data(&mut self) -> Option<ImGuiImeData> {
+        IME_DATA.with(|ime_data| ime_data.take())
+    }
+
+    pub fn mouse_wheel_event(&mut self, lines: cgmath::Vector2<f32>) {
+        self.context.io_mut().add_mouse_wheel_event([lines.x, lines.y]);
+    }
+
     pub fn mouse_pos_event(&mut self, pos: cgmath::Vector2<f32>) {
         self.context.io_mut().add_mouse_pos_event([pos.x, pos.y]);
     }
 
+    /// Recreate the GL objects of the renderer after the context lost them, the font atlas is rebuilt from imgui.
+    /// The old renderer is forgotten, deleting its stale names could delete the new objects.
+    pub fn recreate_renderer(&mut self, capabilities: GLCapabilities) {
+        let renderer = Renderer::new(&mut self.context, capabilities);
+        std::mem::forget(std::mem::replace(&mut self.renderer, renderer));
+    }
+
     pub fn new_frame(&mut self) -> &mut imgui::Ui {
         self.context.new_frame()
     }
@@ -41,7 +97,9 @@ impl ImGui {
 }
 
 // Source: https://docs.rs/crate/imgui-opengl-renderer/0.12.0/source/src/lib.rs
+// Adapted to pick GLSL and VAO/sampler code paths from the context capabilities.
 struct Renderer {
+    capabilities: GLCapabilities,
     program: GLuint,
     locs: Locations,
     vbo: GLuint,
@@ -58,53 +116,43 @@ struct Locations {
 }
 
 impl Renderer {
-    pub fn new(imgui: &mut imgui::Context) -> Self {  
+    pub fn new(imgui: &mut imgui::Context, capabilities: GLCapabilities) -> Self {  
         unsafe {
-            #[cfg(target_os = "macos")]
-            let glsl_version = b"#version 150\n\0";
-            #[cfg(not(target_os = "macos"))]
-            let glsl_version = b"#version 130\n\0";
-            
-            let vert_source = b"
+            let vert_source = capabilities.glsl_version.vertex_source("
                 uniform mat4 ProjMtx;
-                in vec2 Position;
-                in vec2 UV;
-                in vec4 Color;
-                out vec2 Frag_UV;
-                out vec4 Frag_Color;
+                VS_IN vec2 Position;
+                VS_IN vec2 UV;
+                VS_IN vec4 Color;
+                VS_OUT vec2 Frag_UV;
+                VS_OUT vec4 Frag_Color;
                 void main()
                 {
                   Frag_UV = UV;
                   Frag_Color = Color;
-                  gl_Position = ProjMtx * vec4(Position.xy,0,1);
+                  gl_Position = ProjMtx * vec4(Position.xy,0.0,1.0);
                 }
-            \0";
+            ");
             
-            let frag_source = b"
+            let frag_source = capabilities.glsl_version.fragment_source("
                 uniform sampler2D Texture;
-                in vec2 Frag_UV;
-                in vec4 Frag_Color;
-                out vec4 Out_Color;
+                FS_IN vec2 Frag_UV;
+                FS_IN vec4 Frag_Color;
                 void main()
                 {
-                  Out_Color = Frag_Color * texture(Texture, Frag_UV.st);
+                  FRAG_COLOR = Frag_Color * TEXTURE(Texture, Frag_UV.st);
                 }
-            \0";
+            ");
             
-            let vert_sources = [glsl_version.as_ptr() as *const GLchar,
-                                vert_source.as_ptr() as *const GLchar];
-            let vert_sources_len = [glsl_version.len() as GLint - 1,
-                                    vert_source.len() as GLint - 1];
-            let frag_sources = [glsl_version.as_ptr() as *const GLchar,
-                                frag_source.as_ptr() as *const GLchar];
-            let frag_sources_len = [glsl_version.len() as GLint - 1,
-                                    frag_source.len() as GLint - 1];
+            let vert_sources = [vert_source.as_ptr() as *const GLchar];
+            let vert_sources_len = [vert_source.len() as GLint];
+            let frag_sources = [frag_source.as_ptr() as *const GLchar];
+            let frag_sources_len = [frag_source.len() as GLint];
             
             let program = gl::CreateProgram();
             let vert_shader = gl::CreateShader(gl::VERTEX_SHADER);
             let frag_shader = gl::CreateShader(gl::FRAGMENT_SHADER);
-            gl::ShaderSource(vert_shader, 2, vert_sources.as_ptr(), vert_sources_len.as_ptr());
-            gl::ShaderSource(frag_shader, 2, frag_sources.as_ptr(), frag_sources_len.as_ptr());
+            gl::ShaderSource(vert_shader, 1, vert_sources.as_ptr(), vert_sources_len.as_ptr());
+            gl::ShaderSource(frag_shader, 1, frag_sources.as_ptr(), frag_sources_len.as_ptr());
             gl::CompileShader(vert_shader);
             gl::CompileShader(frag_shader);
             gl::AttachShader(program, vert_shader);
@@ -114,11 +162,11 @@ impl Renderer {
             gl::DeleteShader(frag_shader);
             
             let locs = Locations {
-                texture: gl::GetUniformLocation(program, b"Texture\0".as_ptr() as _),
-                proj_mtx: gl::GetUniformLocation(program, b"ProjMtx\0".as_ptr() as _),
-                position: gl::GetAttribLocation(program, b"Position\0".as_ptr() as _) as _,
-                uv: gl::GetAttribLocation(program, b"UV\0".as_ptr() as _) as _,
-                color: gl::GetAttribLocation(program, b"Color\0".as_ptr() as _) as _,
+                texture: gl::GetUniformLocation(program, c"Texture".as_ptr() as _),
+                proj_mtx: gl::GetUniformLocation(program, c"ProjMtx".as_ptr() as _),
+                position: gl::GetAttribLocation(program, c"Position".as_ptr() as _) as _,
+                uv: gl::GetAttribLocation(program, c"UV".as_ptr() as _) as _,
+                color: gl::GetAttribLocation(program, c"Color".as_ptr() as _) as _,
             };
           
             let vbo = return_param(|x| gl::GenBuffers(1, x) );
@@ -131,10 +179,12 @@ impl Renderer {
             gl::BindTexture(gl::TEXTURE_2D, font_texture);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as _);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as _);
-            gl::PixelStorei(gl::UNPACK_ROW_LENGTH, 0);
+            if !capabilities.gles || capabilities.glsl_version == GLSLVersion::Glsl300Es {
+                gl::PixelStorei(gl::UNPACK_ROW_LENGTH, 0);
+            }
           
             {
-                let mut atlas = imgui.fonts();
+                let atlas = imgui.fonts();
 
                 let texture = atlas.build_rgba32_texture();
                 gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as _, texture.width as _, texture.height as _, 0, gl::RGBA, gl::UNSIGNED_BYTE, texture.data.as_ptr() as _);
@@ -145,6 +195,7 @@ impl Renderer {
             gl::BindTexture(gl::TEXTURE_2D, current_texture as _);
           
             Renderer {
+                capabilities,
                 program,
                 locs,
                 vbo,
@@ -162,11 +213,11 @@ impl Renderer {
             gl::ActiveTexture(gl::TEXTURE0);
             let last_program = return_param(|x| gl::GetIntegerv(gl::CURRENT_PROGRAM, x));
             let last_texture = return_param(|x| gl::GetIntegerv(gl::TEXTURE_BINDING_2D, x));
-            let last_sampler = return_param(|x| gl::GetIntegerv(gl::SAMPLER_BINDING, x));
+            let last_sampler = if self.capabilities.samplers { return_param(|x| gl::GetIntegerv(gl::SAMPLER_BINDING, x)) } else { 0 };
             let last_array_buffer = return_param(|x| gl::GetIntegerv(gl::ARRAY_BUFFER_BINDING, x));
             let last_element_array_buffer = return_param(|x| gl::GetIntegerv(gl::ELEMENT_ARRAY_BUFFER_BINDING, x));
-            let last_vertex_array = return_param(|x| gl::GetIntegerv(gl::VERTEX_ARRAY_BINDING, x));
-            let last_polygon_mode = return_param(|x: &mut [GLint; 2]| gl::GetIntegerv(gl::POLYGON_MODE, x.as_mut_ptr()));
+            let last_vertex_array = if self.capabilities.vao { return_param(|x| gl::GetIntegerv(gl::VERTEX_ARRAY_BINDING, x)) } else { 0 };
+            let last_polygon_mode = if self.capabilities.gles { [gl::FILL as GLint; 2] } else { return_param(|x: &mut [GLint; 2]| gl::GetIntegerv(gl::POLYGON_MODE, x.as_mut_ptr())) };
             let last_viewport = return_param(|x: &mut [GLint; 4]| gl::GetIntegerv(gl::VIEWPORT, x.as_mut_ptr()));
             let last_scissor_box = return_param(|x: &mut [GLint; 4]| gl::GetIntegerv(gl::SCISSOR_BOX, x.as_mut_ptr()));
             let last_blend_src_rgb = return_param(|x| gl::GetIntegerv(gl::BLEND_SRC_RGB, x));
@@ -182,11 +233,13 @@ impl Renderer {
             
             gl::Enable(gl::BLEND);
             gl::BlendEquation(gl::FUNC_ADD);
-            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
             gl::Disable(gl::CULL_FACE);
             gl::Disable(gl::DEPTH_TEST);
             gl::Enable(gl::SCISSOR_TEST);
-            gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+            if !self.capabilities.gles {
+                gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+            }
             
             let [width, height] = ctx.io().display_size;
             let [scale_w, scale_h] = ctx.io().display_framebuffer_scale;
@@ -204,10 +257,14 @@ impl Renderer {
             gl::UseProgram(self.program);
             gl::Uniform1i(self.locs.texture, 0);
             gl::UniformMatrix4fv(self.locs.proj_mtx, 1, gl::FALSE, matrix.as_ptr() as _);
-            gl::BindSampler(0, 0);
+            if self.capabilities.samplers {
+                gl::BindSampler(0, 0);
+            }
             
-            let vao = return_param(|x| gl::GenVertexArrays(1, x));
-            gl::BindVertexArray(vao);
+            let vao = if self.capabilities.vao { return_param(|x| gl::GenVertexArrays(1, x)) } else { 0 };
+            if self.capabilities.vao {
+                gl::BindVertexArray(vao);
+            }
             gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
             gl::EnableVertexAttribArray(self.locs.position);
             gl::EnableVertexAttribArray(self.locs.uv);
@@ -223,10 +280,10 @@ impl Renderer {
               let idx_buffer = draw_list.idx_buffer();
 
               gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
-              gl::BufferData(gl::ARRAY_BUFFER, (vtx_buffer.len() * mem::size_of::<DrawVert>()) as _, vtx_buffer.as_ptr() as _, gl::STREAM_DRAW);
+              gl::BufferData(gl::ARRAY_BUFFER, mem::size_of_val(vtx_buffer) as _, vtx_buffer.as_ptr() as _, gl::STREAM_DRAW);
 
               gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
-              gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, (idx_buffer.len() * mem::size_of::<DrawIdx>()) as _, idx_buffer.as_ptr() as _, gl::STREAM_DRAW);
+              gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, mem::size_of_val(idx_buffer) as _, idx_buffer.as_ptr() as _, gl::STREAM_DRAW);
 
               for cmd in draw_list.commands() {
                 match cmd {
@@ -260,13 +317,23 @@ impl Renderer {
               }
             }
           
-            gl::DeleteVertexArrays(1, &vao);
+            if self.capabilities.vao {
+                gl::DeleteVertexArrays(1, &vao);
+            } else {
+                gl::DisableVertexAttribArray(self.locs.position);
+                gl::DisableVertexAttribArray(self.locs.uv);
+                gl::DisableVertexAttribArray(self.locs.color);
+            }
           
             gl::UseProgram(last_program as _);
             gl::BindTexture(gl::TEXTURE_2D, last_texture as _);
-            gl::BindSampler(0, last_sampler as _);
+            if self.capabilities.samplers {
+                gl::BindSampler(0, last_sampler as _);
+            }
             gl::ActiveTexture(last_active_texture as _);
-            gl::BindVertexArray(last_vertex_array as _);
+            if self.capabilities.vao {
+                gl::BindVertexArray(last_vertex_array as _);
+            }
             gl::BindBuffer(gl::ARRAY_BUFFER, last_array_buffer as _);
             gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, last_element_array_buffer as _);
             gl::BlendEquationSeparate(last_blend_equation_rgb as _, last_blend_equation_alpha as _);
@@ -275,7 +342,9 @@ impl Renderer {
             if last_enable_cull_face { gl::Enable(gl::CULL_FACE) } else { gl::Disable(gl::CULL_FACE) };
             if last_enable_depth_test { gl::Enable(gl::DEPTH_TEST) } else { gl::Disable(gl::DEPTH_TEST) };
             if last_enable_scissor_test { gl::Enable(gl::SCISSOR_TEST) } else { gl::Disable(gl::SCISSOR_TEST) };
-            gl::PolygonMode(gl::FRONT_AND_BACK, last_polygon_mode[0] as _);
+            if !self.capabilities.gles {
+                gl::PolygonMode(gl::FRONT_AND_BACK, last_polygon_mode[0] as _);
+            }
             gl::Viewport(last_viewport[0] as _, last_viewport[1] as _, last_viewport[2] as _, last_viewport[3] as _);
             gl::Scissor(last_scissor_box[0] as _, last_scissor_box[1] as _, last_scissor_box[2] as _,  last_scissor_box[3] as _);
         }
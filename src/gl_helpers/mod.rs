@@ -13,20 +13,152 @@ pub mod textures;
 pub use textures::*;
 pub mod imgui_impl;
 pub use imgui_impl::*;
+pub mod swap_interval;
+pub use swap_interval::*;
+pub mod capabilities;
+pub use capabilities::*;
 
 use crate::Window;
+use crate::{GlInfo, GlProfile, parse_gl_version};
 
-pub(crate) fn gl_init(window: &Window) {
-    gl::load_with(|ptr| window.internal_context().get_proc_address(ptr) as *const _);
+pub(crate) fn gl_init<W>(context: &glutin::ContextWrapper<glutin::PossiblyCurrent, W>) {
+    gl::load_with(|ptr| context.get_proc_address(ptr) as *const _);
     gl_check();
 }
 
+/// Query what the current context actually supports.
+pub(crate) fn gl_info<W>(context: &glutin::ContextWrapper<glutin::PossiblyCurrent, W>) -> GlInfo {
+    let version_string = gl_get_string(gl::VERSION);
+    let version = parse_gl_version(&version_string);
+    let gles = context.get_api() == glutin::Api::OpenGlEs;
+
+    // Context flags exist since GL 3.0 and GLES 3.2, the profile mask since GL 3.2.
+    let debug = if (!gles && version >= (3, 0)) || version >= (3, 2) {
+        gl_get_integer(gl::CONTEXT_FLAGS) as u32 & gl::CONTEXT_FLAG_DEBUG_BIT != 0
+    } else {
+        false
+    };
+    let profile = if gles {
+        GlProfile::Gles
+    } else if version >= (3, 2) {
+        let mask = gl_get_integer(gl::CONTEXT_PROFILE_MASK) as u32;
+        if mask & gl::CONTEXT_CORE_PROFILE_BIT != 0 {
+            GlProfile::Core
+        } else if mask & gl::CONTEXT_COMPATIBILITY_PROFILE_BIT != 0 {
+            GlProfile::Compatibility
+        } else {
+            GlProfile::Any
+        }
+    } else {
+        GlProfile::Compatibility
+    };
+
+    let pixel_format = context.get_pixel_format();
+    GlInfo {
+        version,
+        profile,
+        debug,
+        srgb: pixel_format.srgb,
+        samples: pixel_format.multisampling.unwrap_or(0),
+        depth_bits: pixel_format.depth_bits,
+        stencil_bits: pixel_format.stencil_bits,
+        hardware_accelerated: pixel_format.hardware_accelerated,
+        version_string,
+        shading_language_version: gl_get_string(gl::SHADING_LANGUAGE_VERSION),
+        renderer: gl_get_string(gl::RENDERER),
+        vendor: gl_get_string(gl::VENDOR)
+    }
+}
+
+extern "system" fn gl_debug_callback(_source: GLenum, _ty: GLenum, _id: GLuint, severity: GLenum, length: GLsizei, message: *const GLchar, _user: *mut std::ffi::c_void) {
+    if severity == gl::DEBUG_SEVERITY_NOTIFICATION {
+        return;
+    }
+
+    let message = unsafe { std::slice::from_raw_parts(message as *const u8, length as usize) };
+    eprintln!("GL debug: {}", String::from_utf8_lossy(message));
+}
+
+/// Print GL debug messages to stderr, does nothing if `KHR_debug` is not available.
+pub fn gl_enable_debug_output() {
+    if !gl::DebugMessageCallback::is_loaded() {
+        return;
+    }
+
+    unsafe {
+        gl::Enable(gl::DEBUG_OUTPUT);
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(Some(gl_debug_callback), std::ptr::null());
+        gl_check();
+    }
+}
+
+/// Get the reset status of a robust context, `GL_NO_ERROR` if the context was not reset or robustness isn't supported.
+pub fn gl_graphics_reset_status() -> GLenum {
+    if !gl::GetGraphicsResetStatus::is_loaded() {
+        return gl::NO_ERROR;
+    }
+
+    unsafe {
+        gl::GetGraphicsResetStatus()
+    }
+}
+
+pub fn gl_get_string(name: GLenum) -> String {
+    unsafe {
+        let string = gl::GetString(name);
+        gl_check();
+        if string.is_null() {
+            return String::new();
+        }
+        std::ffi::CStr::from_ptr(string as *const std::ffi::c_char).to_string_lossy().into_owned()
+    }
+}
+
+pub fn gl_get_integer(name: GLenum) -> i32 {
+    let mut value = 0;
+    unsafe {
+        gl::GetIntegerv(name, &mut value);
+        gl_check();
+    }
+    value
+}
+
+/// Check if the current context exposes an extension, `version` selects between the indexed query of GL 3.0 / GLES 3.0 and the legacy string.
+pub fn gl_has_extension(version: (u8, u8), name: &str) -> bool {
+    if version >= (3, 0) && gl::GetStringi::is_loaded() {
+        let count = gl_get_integer(gl::NUM_EXTENSIONS).max(0) as u32;
+        (0..count).any(|i| unsafe {
+            let extension = gl::GetStringi(gl::EXTENSIONS, i);
+            gl_check();
+            !extension.is_null() && std::ffi::CStr::from_ptr(extension as *const std::ffi::c_char).to_bytes() == name.as_bytes()
+        })
+    } else {
+        gl_get_string(gl::EXTENSIONS).split_whitespace().any(|extension| extension == name)
+    }
+}
+
+/// Only call when `GLCapabilities::framebuffer_srgb` is set, the enum is invalid otherwise.
+pub fn gl_framebuffer_srgb(enable: bool) {
+    unsafe {
+        if enable {
+            gl::Enable(gl::FRAMEBUFFER_SRGB);
+        } else {
+            gl::Disable(gl::FRAMEBUFFER_SRGB);
+        }
+        gl_check();
+    }
+}
+
 #[cfg(debug_assertions)]
 fn gl_check() {
     unsafe {
         let error = gl::GetError();
         match error {
             gl::NO_ERROR => (),
+            // A reset can happen mid-frame, `Renderer::recover_context_loss` rebuilds the context at the start of the next one.
+            gl::CONTEXT_LOST => (),
+            _ if gl_graphics_reset_status() != gl::NO_ERROR => (),
             gl::INVALID_ENUM => panic!("GL invalid enum."),
             gl::INVALID_VALUE => panic!("GL invalid value."),
             gl::INVALID_OPERATION => panic!("GL invalid operation."),
@@ -56,6 +188,22 @@ pub fn gl_cull(mode: GLenum) {
     }
 }
 
+pub fn gl_enable_blend() {
+    unsafe {
+        gl::Enable(gl::BLEND);
+        // Keep the framebuffer alpha premultiplied for transparent windows.
+        gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+        gl_check();
+    }
+}
+
+pub fn gl_disable_blend() {
+    unsafe {
+        gl::Disable(gl::BLEND);
+        gl_check();
+    }
+}
+
 pub fn gl_clear_color(color: cgmath::Vector3<f32>) {
     unsafe {
         gl::ClearColor(color.x, color.y, color.z, 1.0f32);
@@ -63,6 +211,13 @@ pub fn gl_clear_color(color: cgmath::Vector3<f32>) {
     }
 }
 
+pub fn gl_clear_color_alpha(color: cgmath::Vector4<f32>) {
+    unsafe {
+        gl::ClearColor(color.x, color.y, color.z, color.w);
+        gl_check();
+    }
+}
+
 pub fn gl_clear() {
     unsafe {
         gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
@@ -77,6 +232,13 @@ pub fn gl_viewport(width: u32, height: u32) {
     }
 }
 
+pub fn gl_viewport_rect(x: i32, y: i32, width: u32, height: u32) {
+    unsafe {
+        gl::Viewport(x, y, width as i32, height as i32);
+        gl_check();
+    }
+}
+
 pub fn gl_draw_elems(mode: GLenum, count: usize, index_type: GLenum) {
     unsafe {
         gl::DrawElements(mode, count as i32, index_type, std::ptr::null());
@@ -91,6 +253,24 @@ pub fn gl_draw_arrays(mode: GLenum, offset: usize, count: usize) {
     }
 }
 
+/// Read RGBA pixels of the bound read framebuffer into `data`, or at offset `data` of the bound pack PBO.
+pub fn gl_read_pixels(width: u32, height: u32, data: *mut std::ffi::c_void) {
+    unsafe {
+        gl::ReadPixels(0, 0, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE, data);
+        gl_check();
+    }
+}
+
+/// Read the alpha of one pixel of the bound read framebuffer, (0, 0) is the bottom left.
+pub fn gl_read_pixel_alpha(x: i32, y: i32) -> u8 {
+    let mut rgba = [0u8; 4];
+    unsafe {
+        gl::ReadPixels(x, y, 1, 1, gl::RGBA, gl::UNSIGNED_BYTE, rgba.as_mut_ptr() as *mut std::ffi::c_void);
+        gl_check();
+    }
+    rgba[3]
+}
+
 pub fn gl_finish() {
     unsafe {
         gl::Finish();
@@ -178,6 +178,20 @@ impl GLShaderProgram {
         self.set_int(name, value);
     }
 
+    pub fn attrib_location(&self, name: &str) -> u32 {
+        let mut cname = name.to_owned();
+        cname.push('\0');
+
+        unsafe {
+            let location = gl::GetAttribLocation(self.buffer, cname.as_ptr() as *const GLchar);
+            gl_check();
+            if location < 0 {
+                eprintln!("Failed to get attribute location. (Name: '{}')", name);
+            }
+            location.max(0) as u32
+        }
+    }
+
     fn uniform_location(&mut self, name: &String) -> i32 {
         match self.uniform_locations.get(name) {
             Some(location) => *location,
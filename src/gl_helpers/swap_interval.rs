@@ -0,0 +1,135 @@
+use super::*;
+
+/*****************************************************************************
+*                               FUNCS
+******************************************************************************/
+
+/// Set the swap interval of the current context, returns `false` if the platform doesn't support it.
+/// Glutin 0.29 only supports vsync at creation so the platform extensions are loaded directly.
+/// The extension strings are checked first, calling a swap interval function the driver doesn't implement is an X error that aborts the process.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+pub fn gl_swap_interval(window: &Window, interval: i32) -> bool {
+    use glutin::platform::ContextTraitExt;
+    use glutin::platform::unix::WindowExtUnix;
+    use std::ffi::{c_void, CStr};
+    use std::os::raw::{c_char, c_int, c_ulong};
+
+    const EGL_EXTENSIONS: i32 = 0x3055;
+
+    let context = window.internal_context();
+
+    unsafe {
+        if let Some(display) = context.get_egl_display() {
+            // EGL has no adaptive vsync, negative intervals are silently clamped.
+            if interval < 0 {
+                return false;
+            }
+
+            let swap_interval = context.get_proc_address("eglSwapInterval");
+            if swap_interval.is_null() {
+                return false;
+            }
+
+            let swap_interval: extern "C" fn(*const c_void, i32) -> u32 = std::mem::transmute(swap_interval);
+            return swap_interval(display, interval) != 0;
+        }
+
+        let winit_window = window.internal_window();
+        let (display, drawable, screen) = match (winit_window.xlib_display(), winit_window.xlib_window(), winit_window.xlib_screen_id()) {
+            (Some(display), Some(drawable), Some(screen)) => (display, drawable, screen),
+            _ => return false
+        };
+
+        let query_extensions = context.get_proc_address("glXQueryExtensionsString");
+        if query_extensions.is_null() {
+            return false;
+        }
+        let query_extensions: extern "C" fn(*mut c_void, c_int) -> *const c_char = std::mem::transmute(query_extensions);
+        let extensions = query_extensions(display, screen);
+        if extensions.is_null() {
+            return false;
+        }
+        let extensions = CStr::from_ptr(extensions).to_string_lossy();
+
+        if has_extension(&extensions, "GLX_EXT_swap_control") && (interval >= 0 || has_extension(&extensions, "GLX_EXT_swap_control_tear")) {
+            let swap_interval = context.get_proc_address("glXSwapIntervalEXT");
+            let swap_interval: extern "C" fn(*mut c_void, c_ulong, i32) = std::mem::transmute(swap_interval);
+            swap_interval(display, drawable, interval);
+            return true;
+        }
+
+        if has_extension(&extensions, "GLX_MESA_swap_control") && interval >= 0 {
+            let swap_interval = context.get_proc_address("glXSwapIntervalMESA");
+            let swap_interval: extern "C" fn(u32) -> i32 = std::mem::transmute(swap_interval);
+            return swap_interval(interval as u32) == 0;
+        }
+    }
+
+    false
+}
+
+/// Set the swap interval of the current context, returns `false` if the platform doesn't support it.
+/// Glutin 0.29 only supports vsync at creation so the platform extensions are loaded directly.
+#[cfg(target_os = "windows")]
+pub fn gl_swap_interval(window: &Window, interval: i32) -> bool {
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    let context = window.internal_context();
+
+    unsafe {
+        let get_extensions = context.get_proc_address("wglGetExtensionsStringEXT");
+        if get_extensions.is_null() {
+            return false;
+        }
+        let get_extensions: extern "system" fn() -> *const c_char = std::mem::transmute(get_extensions);
+        let extensions = get_extensions();
+        if extensions.is_null() {
+            return false;
+        }
+        let extensions = CStr::from_ptr(extensions).to_string_lossy();
+
+        if !has_extension(&extensions, "WGL_EXT_swap_control") || (interval < 0 && !has_extension(&extensions, "WGL_EXT_swap_control_tear")) {
+            return false;
+        }
+
+        let swap_interval = context.get_proc_address("wglSwapIntervalEXT");
+        if swap_interval.is_null() {
+            return false;
+        }
+
+        let swap_interval: extern "system" fn(i32) -> i32 = std::mem::transmute(swap_interval);
+        swap_interval(interval) != 0
+    }
+}
+
+/// Set the swap interval of the current context, returns `false` if the platform doesn't support it.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows",
+)))]
+pub fn gl_swap_interval(_window: &Window, _interval: i32) -> bool {
+    false
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows",
+))]
+fn has_extension(extensions: &str, name: &str) -> bool {
+    extensions.split_whitespace().any(|extension| extension == name)
+}
@@ -1,12 +1,21 @@
-pub use glutin::event::{MouseButton, VirtualKeyCode};
+pub use glutin::event::{MouseButton, VirtualKeyCode, TouchPhase};
+use glutin::event::{MouseScrollDelta, Touch};
+
+use std::time::{Duration, Instant};
 
 use cgmath::Vector2;
 use crate::RcCell;
 use crate::Window;
+use crate::{RenderTexture, RenderTextureType};
 use crate::gl_helpers::ImGui;
+use crate::{ModifiersState, Shortcut, ShortcutTrigger};
+use crate::gamepad::Gamepads;
+use crate::{GamepadAxis, GamepadBackend, GamepadButton, GamepadConnection, GamepadId, VirtualGamepad};
 
 const MAX_KEYS: usize = 512;
-const MAX_BUTTONS: usize = 32;
+pub(crate) const MAX_BUTTONS: usize = 32;
+/// Logical pixels of trackpad scrolling that count as one line for imgui.
+const PIXELS_PER_LINE: f32 = 20.0;
 
 /// The cursor mode.
 /// - `FREE` the cursor is not restrained in any way.
@@ -17,29 +26,208 @@ pub enum CursorMode {
     LOCKED
 }
 
+/// Scroll distance of a frame, positive values scroll up and left (the content moves down and right).
+/// Mouse wheels usually report `lines`, trackpads and touch screens report `pixels` in physical pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScrollDelta {
+    pub lines: Vector2<f32>,
+    pub pixels: Vector2<f32>
+}
+
+impl Default for ScrollDelta {
+    fn default() -> Self {
+        ScrollDelta {
+            lines: Vector2::new(0.0, 0.0),
+            pixels: Vector2::new(0.0, 0.0)
+        }
+    }
+}
+
+/// A finger on a touchscreen, see `Input::touches`.
+/// - `id` stays the same while the finger touches the screen.
+/// - `phase` is `Started` the frame the touch begins, `Moved` while it is held and `Ended` or `Cancelled` the frame it lifts.
+/// - `position` is in physical pixels relative to the top left corner of the window.
+/// - `force` ranges from `0.0` to `1.0`, `None` if the touchscreen doesn't report pressure.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TouchPoint {
+    pub id: u64,
+    pub phase: TouchPhase,
+    pub position: Vector2<f32>,
+    pub force: Option<f32>
+}
+
+/// Where the primary touch moves the mouse and if it presses (`Some(true)`) or releases (`Some(false)`) the left button.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct TouchMouse {
+    position: Vector2<f32>,
+    button: Option<bool>
+}
+
+/// The touch points of a frame and the primary touch, the first finger that touched the screen while no other finger did.
+struct TouchTracker {
+    touches: Vec<TouchPoint>,
+    primary: Option<u64>
+}
+
+impl TouchTracker {
+    fn new() -> Self {
+        TouchTracker {
+            touches: Vec::new(),
+            primary: None
+        }
+    }
+
+    fn update(&mut self) {
+        self.touches.retain(|touch| !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled));
+        for touch in &mut self.touches {
+            touch.phase = TouchPhase::Moved;
+        }
+    }
+
+    /// Track a touch point, returns what it does to the mouse if it is the primary touch.
+    fn add(&mut self, point: TouchPoint) -> Option<TouchMouse> {
+        match self.touches.iter_mut().find(|existing| existing.id == point.id) {
+            // A touch that started this frame stays `Started` until the next frame.
+            Some(existing) if existing.phase == TouchPhase::Started && point.phase == TouchPhase::Moved => {
+                existing.position = point.position;
+                existing.force = point.force;
+            },
+            Some(existing) => *existing = point,
+            None => self.touches.push(point)
+        }
+
+        if point.phase == TouchPhase::Started && self.primary.is_none() {
+            self.primary = Some(point.id);
+        }
+        if self.primary != Some(point.id) {
+            return None;
+        }
+
+        let button = match point.phase {
+            TouchPhase::Started => Some(true),
+            TouchPhase::Moved => None,
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.primary = None;
+                Some(false)
+            }
+        };
+
+        Some(TouchMouse {
+            position: point.position,
+            button
+        })
+    }
+
+    fn touches(&self) -> &[TouchPoint] {
+        &self.touches
+    }
+}
+
+/// What happened in an `InputEvent`.
+/// - `Key` a key was pressed or released, `repeat` is set for presses generated by holding the key.
+/// - `MouseButton` a mouse button was pressed or released.
+/// - `MouseMove` the mouse moved to a position in physical pixels.
+/// - `Scroll` the mouse wheel or trackpad scrolled, see `ScrollDelta`.
+/// - `Text` text was typed or committed by the IME, filtered like `Input::text`.
+/// - `Touch` a touch point changed, see `TouchPoint`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEventKind {
+    Key { key_code: VirtualKeyCode, pressed: bool, repeat: bool },
+    MouseButton { button: MouseButton, pressed: bool },
+    MouseMove { position: Vector2<i32> },
+    Scroll(ScrollDelta),
+    Text(String),
+    Touch(TouchPoint)
+}
+
+/// An input event and the time the event loop received it, see `Input::events`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputEvent {
+    pub time: Instant,
+    pub kind: InputEventKind
+}
+
+/// The input events of a frame in the order they were received.
+struct EventQueue {
+    events: Vec<InputEvent>
+}
+
+impl EventQueue {
+    fn new() -> Self {
+        EventQueue {
+            events: Vec::new()
+        }
+    }
+
+    fn push(&mut self, kind: InputEventKind) {
+        self.events.push(InputEvent {
+            time: Instant::now(),
+            kind
+        });
+    }
+
+    fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+}
+
 /// Input manager
 pub struct Input {
     window: RcCell<Window>,
 
     keys: [bool; MAX_KEYS],
     keys_prev: [bool; MAX_KEYS],
+    keys_pressed_at: [Option<Instant>; MAX_KEYS],
     buttons: [bool; MAX_BUTTONS],
     buttons_prev: [bool; MAX_BUTTONS],
+    buttons_pressed_at: [Option<Instant>; MAX_BUTTONS],
     mouse_pos: Vector2<i32>,
     mouse_delta: Vector2<f32>,
+    mouse_inside: bool,
+    modifiers: ModifiersState,
+    scroll_delta: ScrollDelta,
+    ime_preedit: String,
+    ime_preedit_cursor: Option<(usize, usize)>,
+    ime_commit: String,
+    text: String,
+    events: EventQueue,
+    touches: TouchTracker,
+    touch_mouse_emulation: bool,
+    gamepads: Gamepads,
+    scale_factor: f64,
     cursor_mode: CursorMode
 }
 
 impl Input {
     pub(crate) fn new(window: RcCell<Window>) -> RcCell<Self> {
+        let scale_factor = window.as_ref().get_scale_factor();
+
         RcCell::new(Input {
             window,
             keys: [false; MAX_KEYS],
             keys_prev: [false; MAX_KEYS],
+            keys_pressed_at: [None; MAX_KEYS],
             buttons: [false; MAX_BUTTONS],
             buttons_prev: [false; MAX_BUTTONS],
+            buttons_pressed_at: [None; MAX_BUTTONS],
             mouse_pos: Vector2::new(0, 0),
             mouse_delta: Vector2::new(0.0, 0.0),
+            mouse_inside: false,
+            modifiers: ModifiersState::empty(),
+            scroll_delta: ScrollDelta::default(),
+            ime_preedit: String::new(),
+            ime_preedit_cursor: None,
+            ime_commit: String::new(),
+            text: String::new(),
+            events: EventQueue::new(),
+            touches: TouchTracker::new(),
+            touch_mouse_emulation: false,
+            gamepads: Gamepads::new(),
+            scale_factor,
             cursor_mode: CursorMode::FREE
         })
     }
@@ -48,6 +236,12 @@ impl Input {
         self.keys_prev = self.keys;
         self.buttons_prev = self.buttons;
         self.mouse_delta = Vector2::new(0.0, 0.0);
+        self.scroll_delta = ScrollDelta::default();
+        self.ime_commit.clear();
+        self.text.clear();
+        self.events.clear();
+        self.gamepads.update();
+        self.touches.update();
     }
 
     /// Check if key is pressed.
@@ -60,26 +254,189 @@ impl Input {
         self.keys[key_code as usize] && !self.keys_prev[key_code as usize]
     }
 
-    /// Check if mouse button is pressed.
+    /// Check if key is not pressed AND was pressed previous frame.
+    pub fn key_up(&self, key_code: VirtualKeyCode) -> bool {
+        !self.keys[key_code as usize] && self.keys_prev[key_code as usize]
+    }
+
+    /// Get how long key has been pressed, zero if it is not pressed.
+    pub fn key_held_for(&self, key_code: VirtualKeyCode) -> Duration {
+        self.keys_pressed_at[key_code as usize].map_or(Duration::ZERO, |pressed_at| pressed_at.elapsed())
+    }
+
+    /// Check if mouse button is pressed, buttons past `MouseButton::Other(28)` are never pressed.
     pub fn mouse_button(&self, button: MouseButton) -> bool {
-        self.buttons[Self::mb_to_idx(button)]
+        Self::mb_to_idx(button).is_some_and(|idx| self.buttons[idx])
     }
 
     /// Check if mouse button is pressed AND was not pressed previous frame.
     pub fn mouse_button_down(&self, button: MouseButton) -> bool {
-        self.buttons[Self::mb_to_idx(button)] && !self.buttons_prev[Self::mb_to_idx(button)]
+        Self::mb_to_idx(button).is_some_and(|idx| self.buttons[idx] && !self.buttons_prev[idx])
+    }
+
+    /// Check if mouse button is not pressed AND was pressed previous frame.
+    pub fn mouse_button_up(&self, button: MouseButton) -> bool {
+        Self::mb_to_idx(button).is_some_and(|idx| !self.buttons[idx] && self.buttons_prev[idx])
+    }
+
+    /// Get how long mouse button has been pressed, zero if it is not pressed.
+    pub fn mouse_button_held_for(&self, button: MouseButton) -> Duration {
+        Self::mb_to_idx(button)
+            .and_then(|idx| self.buttons_pressed_at[idx])
+            .map_or(Duration::ZERO, |pressed_at| pressed_at.elapsed())
     }
 
-    /// Get current mouse position in window space.
+    /// Get current mouse position in window space, in physical pixels.
     pub fn mouse_pos(&self) -> Vector2<i32> {
         self.mouse_pos
     }
 
+    /// Get current mouse position in window space, in logical pixels.
+    pub fn logical_mouse_pos(&self) -> Vector2<f32> {
+        let scale_factor = self.scale_factor as f32;
+        Vector2::new(self.mouse_pos.x as f32 / scale_factor, self.mouse_pos.y as f32 / scale_factor)
+    }
+
+    /// Get the render texture pixel under the mouse, `None` if the mouse is outside of the viewport.
+    /// See `RenderTexture::window_to_pixel`.
+    pub fn mouse_pos_in<T: RenderTextureType>(&self, render_texture: &RenderTexture<T>) -> Option<Vector2<u32>> {
+        let mouse_pos = Vector2::new(self.mouse_pos.x as f32, self.mouse_pos.y as f32);
+        render_texture.window_to_pixel(&self.window.as_ref(), mouse_pos)
+    }
+
+    /// Check if the mouse is inside the window.
+    pub fn mouse_inside(&self) -> bool {
+        self.mouse_inside
+    }
+
     /// Get mouse velocity.
     pub fn mouse_delta(&self) -> Vector2<f32> {
         self.mouse_delta
     }
 
+    /// Get the modifier keys that are currently pressed.
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Check if the shortcut was pressed this frame with exactly its modifiers, e.g. Ctrl+S doesn't match Ctrl+Shift+S.
+    pub fn shortcut_pressed(&self, shortcut: Shortcut) -> bool {
+        let pressed = match shortcut.trigger {
+            ShortcutTrigger::Key(key_code) => self.key_down(key_code),
+            ShortcutTrigger::MouseButton(button) => self.mouse_button_down(button)
+        };
+
+        pressed && self.modifiers == shortcut.modifiers
+    }
+
+    /// Get the distance scrolled this frame with the mouse wheel or trackpad, horizontal scrolling is the `x` component.
+    pub fn scroll_delta(&self) -> ScrollDelta {
+        self.scroll_delta
+    }
+
+    /// Get the text that is currently being composed with the IME, empty if there is no composition.
+    pub fn ime_preedit(&self) -> &str {
+        &self.ime_preedit
+    }
+
+    /// Get the byte range of the cursor within `ime_preedit()`, `None` if the cursor should be hidden.
+    pub fn ime_preedit_cursor(&self) -> Option<(usize, usize)> {
+        self.ime_preedit_cursor
+    }
+
+    /// Get the text the IME committed this frame.
+    pub fn ime_commit(&self) -> &str {
+        &self.ime_commit
+    }
+
+    /// Get the text typed this frame, following the keyboard layout and including the text committed by the IME.
+    /// Enter is reported as `'\n'` and Tab as `'\t'`, other control characters like Backspace, Delete, Escape and Ctrl shortcuts are left out, check their keys instead.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the touch points that are on the screen, including the ones that lifted this frame.
+    pub fn touches(&self) -> &[TouchPoint] {
+        self.touches.touches()
+    }
+
+    /// Check if the first finger that touches the screen is reported as the left mouse button and the mouse position.
+    pub fn get_touch_mouse_emulation(&self) -> bool {
+        self.touch_mouse_emulation
+    }
+
+    /// Report the first finger that touches the screen as the left mouse button and the mouse position, disabled by default.
+    /// ImGui always receives the first finger as its mouse.
+    pub fn set_touch_mouse_emulation(&mut self, emulate: bool) {
+        self.touch_mouse_emulation = emulate;
+    }
+
+    /// Get the gamepads that are currently connected.
+    pub fn gamepads(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.gamepads.ids()
+    }
+
+    /// Get the gamepads that connected or disconnected this frame.
+    pub fn gamepad_connection_events(&self) -> &[(GamepadId, GamepadConnection)] {
+        self.gamepads.connection_events()
+    }
+
+    /// Check if gamepad is connected.
+    pub fn gamepad_connected(&self, id: GamepadId) -> bool {
+        self.gamepads.connected(id)
+    }
+
+    /// Get the name the gamepad reported when it connected.
+    pub fn gamepad_name(&self, id: GamepadId) -> Option<&str> {
+        self.gamepads.name(id)
+    }
+
+    /// Check if gamepad button is pressed.
+    pub fn gamepad_button(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads.button(id, button)
+    }
+
+    /// Check if gamepad button is pressed AND was not pressed previous frame.
+    pub fn gamepad_button_down(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads.button_down(id, button)
+    }
+
+    /// Check if gamepad button is not pressed AND was pressed previous frame.
+    pub fn gamepad_button_up(&self, id: GamepadId, button: GamepadButton) -> bool {
+        self.gamepads.button_up(id, button)
+    }
+
+    /// Get the value of a gamepad axis with the deadzone applied, zero if the gamepad is not connected.
+    pub fn gamepad_axis(&self, id: GamepadId, axis: GamepadAxis) -> f32 {
+        self.gamepads.axis(id, axis)
+    }
+
+    /// Get the gamepad deadzone.
+    pub fn get_gamepad_deadzone(&self) -> f32 {
+        self.gamepads.get_deadzone()
+    }
+
+    /// Set the gamepad deadzone, axis values closer to zero read as zero and the rest is rescaled to start at zero. Defaults to `0.1`.
+    pub fn set_gamepad_deadzone(&mut self, deadzone: f32) {
+        self.gamepads.set_deadzone(deadzone);
+    }
+
+    /// Add a source of gamepad events next to the platform backend.
+    pub fn add_gamepad_backend(&mut self, backend: Box<dyn GamepadBackend>) {
+        self.gamepads.add_backend(backend);
+    }
+
+    /// Connect a software gamepad that can be driven through the returned handle, it shows up in `gamepads()` next frame.
+    pub fn connect_virtual_gamepad(&mut self, name: impl Into<String>) -> VirtualGamepad {
+        self.gamepads.connect_virtual(name)
+    }
+
+    /// Get the input events of this frame in the order they happened.
+    /// Unlike the polled state, presses and releases that happen within one frame are all kept.
+    pub fn events(&self) -> &[InputEvent] {
+        self.events.events()
+    }
+
     /// Get current cursor mode.
     pub fn get_cursor_mode(&self) -> CursorMode {
         self.cursor_mode
@@ -87,21 +444,21 @@ impl Input {
 
     /// Set current cursor mode.
     pub fn set_cursor_mode(&mut self, mode: CursorMode) {
-        let window = self.window.as_ref();
+        let mut window = self.window.as_mut();
         let winit_window = window.internal_window();
 
         match mode {
             CursorMode::FREE => {
                 winit_window.set_cursor_grab(glutin::window::CursorGrabMode::None)
                     .expect("Failed to free cursor.");
-                winit_window.set_cursor_visible(true);
+                window.set_cursor_grabbed(false);
             },
             CursorMode::LOCKED => {
                 let _ = winit_window.set_cursor_grab(glutin::window::CursorGrabMode::Confined)
                     .and_then(|_| {
                         winit_window.set_cursor_grab(glutin::window::CursorGrabMode::Locked)
                     });
-                    winit_window.set_cursor_visible(false);
+                window.set_cursor_grabbed(true);
             }
         }
 
@@ -117,33 +474,179 @@ impl Input {
         }
     }
 
-    pub(crate) fn set_key(&mut self, key_code: VirtualKeyCode, value: bool) {
+    pub(crate) fn poll_gamepads(&mut self) {
+        self.gamepads.poll();
+    }
+
+    pub(crate) fn set_key(&mut self, key_code: VirtualKeyCode, value: bool, imgui: &mut ImGui) {
+        self.push_event(key_event(key_code, value, self.keys[key_code as usize]));
+
+        // Key repeats keep the time of the initial press.
+        if !value {
+            self.keys_pressed_at[key_code as usize] = None;
+        } else if !self.keys[key_code as usize] {
+            self.keys_pressed_at[key_code as usize] = Some(Instant::now());
+        }
         self.keys[key_code as usize] = value;
+
+        if let Some(key) = winit_to_imgui_key(key_code) {
+            imgui.key_event(key, value);
+        }
+    }
+
+    pub(crate) fn set_modifiers(&mut self, modifiers: ModifiersState, imgui: &mut ImGui) {
+        self.modifiers = modifiers;
+
+        imgui.key_event(imgui::Key::ModCtrl, modifiers.ctrl());
+        imgui.key_event(imgui::Key::ModShift, modifiers.shift());
+        imgui.key_event(imgui::Key::ModAlt, modifiers.alt());
+        imgui.key_event(imgui::Key::ModSuper, modifiers.logo());
     }
 
     pub(crate) fn set_mouse_button(&mut self, button: MouseButton, value: bool, imgui: &mut ImGui) {
-        self.buttons[Self::mb_to_idx(button)] = value;
+        // Mice can report any button number, the ones without a slot only show up in `events()`.
+        if let Some(idx) = Self::mb_to_idx(button) {
+            if !value {
+                self.buttons_pressed_at[idx] = None;
+            } else if !self.buttons[idx] {
+                self.buttons_pressed_at[idx] = Some(Instant::now());
+            }
+            self.buttons[idx] = value;
+        }
+        self.push_event(InputEventKind::MouseButton { button, pressed: value });
 
         imgui.mouse_button_event(winit_to_imgui_mouse_button(button), value);
     }
 
     pub(crate) fn set_mouse_pos(&mut self, mouse_pos: Vector2<i32>, imgui: &mut ImGui) {
         self.mouse_pos = mouse_pos;
+        self.push_event(InputEventKind::MouseMove { position: mouse_pos });
 
-        imgui.mouse_pos_event(cgmath::Vector2::new(mouse_pos.x as f32, mouse_pos.y as f32));
+        imgui.mouse_pos_event(self.logical_mouse_pos());
+    }
+
+    pub(crate) fn add_touch(&mut self, touch: Touch, imgui: &mut ImGui) {
+        let point = TouchPoint {
+            id: touch.id,
+            phase: touch.phase,
+            position: Vector2::new(touch.location.x as f32, touch.location.y as f32),
+            force: touch.force.map(|force| force.normalized() as f32)
+        };
+
+        self.push_event(InputEventKind::Touch(point));
+
+        let mouse = match self.touches.add(point) {
+            Some(mouse) => mouse,
+            None => return
+        };
+
+        if self.touch_mouse_emulation {
+            self.set_mouse_pos(Vector2::new(mouse.position.x as i32, mouse.position.y as i32), imgui);
+            if let Some(pressed) = mouse.button {
+                self.set_mouse_button(MouseButton::Left, pressed, imgui);
+            }
+        } else {
+            imgui.mouse_pos_event(mouse.position / self.scale_factor as f32);
+            if let Some(pressed) = mouse.button {
+                imgui.mouse_button_event(imgui::MouseButton::Left, pressed);
+            }
+        }
+    }
+
+    pub(crate) fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    pub(crate) fn set_ime_preedit(&mut self, preedit: String, cursor: Option<(usize, usize)>) {
+        self.ime_preedit = preedit;
+        self.ime_preedit_cursor = cursor;
+    }
+
+    pub(crate) fn add_ime_commit(&mut self, commit: &str, imgui: &mut ImGui) {
+        self.ime_commit.push_str(commit);
+        let text = filter_text(commit);
+        self.text.push_str(&text);
+        if !text.is_empty() {
+            self.push_event(InputEventKind::Text(text));
+        }
+
+        for character in commit.chars() {
+            imgui.character_event(character);
+        }
+    }
+
+    pub(crate) fn add_character(&mut self, character: char, imgui: &mut ImGui) {
+        if let Some(character) = text_character(character) {
+            self.text.push(character);
+            self.push_event(InputEventKind::Text(character.to_string()));
+        }
+        imgui.character_event(character);
+    }
+
+    pub(crate) fn add_scroll(&mut self, delta: MouseScrollDelta, imgui: &mut ImGui) {
+        let mut scroll = ScrollDelta::default();
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(x, y) => {
+                scroll.lines = Vector2::new(x, y);
+                scroll.lines
+            },
+            MouseScrollDelta::PixelDelta(position) => {
+                scroll.pixels = Vector2::new(position.x as f32, position.y as f32);
+                scroll.pixels / (PIXELS_PER_LINE * self.scale_factor as f32)
+            }
+        };
+        self.scroll_delta.lines += scroll.lines;
+        self.scroll_delta.pixels += scroll.pixels;
+        self.push_event(InputEventKind::Scroll(scroll));
+
+        imgui.mouse_wheel_event(lines);
+    }
+
+    pub(crate) fn set_mouse_inside(&mut self, inside: bool) {
+        self.mouse_inside = inside;
     }
 
     pub(crate) fn set_mouse_delta(&mut self, mouse_delta: Vector2<f32>) {
         self.mouse_delta = mouse_delta;
     }
 
-    fn mb_to_idx(button: MouseButton) -> usize {
-        match button {
+    fn push_event(&mut self, kind: InputEventKind) {
+        self.events.push(kind);
+    }
+
+    /// Get the state slot of a mouse button, `None` if there are more buttons than `MAX_BUTTONS`.
+    fn mb_to_idx(button: MouseButton) -> Option<usize> {
+        let idx = match button {
             MouseButton::Right => 0,
             MouseButton::Middle => 1,
             MouseButton::Left => 2,
             MouseButton::Other(i) => 3 + i as usize
-        }
+        };
+        (idx < MAX_BUTTONS).then_some(idx)
+    }
+}
+
+/// Map a received character to the character it adds to `Input::text()`, `None` for control characters.
+fn text_character(character: char) -> Option<char> {
+    match character {
+        '\r' | '\n' => Some('\n'),
+        '\t' => Some('\t'),
+        character if character.is_control() => None,
+        character => Some(character)
+    }
+}
+
+/// Filter received text to what it adds to `Input::text()`.
+fn filter_text(text: &str) -> String {
+    text.chars().filter_map(text_character).collect()
+}
+
+/// The event of a key press or release, a press of a key that is already pressed is a repeat.
+fn key_event(key_code: VirtualKeyCode, pressed: bool, was_pressed: bool) -> InputEventKind {
+    InputEventKind::Key {
+        key_code,
+        pressed,
+        repeat: pressed && was_pressed
     }
 }
 
@@ -159,4 +662,267 @@ fn winit_to_imgui_mouse_button(button: MouseButton) -> imgui::MouseButton {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+fn winit_to_imgui_key(key_code: VirtualKeyCode) -> Option<imgui::Key> {
+    Some(match key_code {
+        VirtualKeyCode::Tab => imgui::Key::Tab,
+        VirtualKeyCode::Left => imgui::Key::LeftArrow,
+        VirtualKeyCode::Right => imgui::Key::RightArrow,
+        VirtualKeyCode::Up => imgui::Key::UpArrow,
+        VirtualKeyCode::Down => imgui::Key::DownArrow,
+        VirtualKeyCode::PageUp => imgui::Key::PageUp,
+        VirtualKeyCode::PageDown => imgui::Key::PageDown,
+        VirtualKeyCode::Home => imgui::Key::Home,
+        VirtualKeyCode::End => imgui::Key::End,
+        VirtualKeyCode::Insert => imgui::Key::Insert,
+        VirtualKeyCode::Delete => imgui::Key::Delete,
+        VirtualKeyCode::Back => imgui::Key::Backspace,
+        VirtualKeyCode::Space => imgui::Key::Space,
+        VirtualKeyCode::Return => imgui::Key::Enter,
+        VirtualKeyCode::Escape => imgui::Key::Escape,
+        VirtualKeyCode::LControl => imgui::Key::LeftCtrl,
+        VirtualKeyCode::LShift => imgui::Key::LeftShift,
+        VirtualKeyCode::LAlt => imgui::Key::LeftAlt,
+        VirtualKeyCode::LWin => imgui::Key::LeftSuper,
+        VirtualKeyCode::RControl => imgui::Key::RightCtrl,
+        VirtualKeyCode::RShift => imgui::Key::RightShift,
+        VirtualKeyCode::RAlt => imgui::Key::RightAlt,
+        VirtualKeyCode::RWin => imgui::Key::RightSuper,
+        VirtualKeyCode::Key0 => imgui::Key::Alpha0,
+        VirtualKeyCode::Key1 => imgui::Key::Alpha1,
+        VirtualKeyCode::Key2 => imgui::Key::Alpha2,
+        VirtualKeyCode::Key3 => imgui::Key::Alpha3,
+        VirtualKeyCode::Key4 => imgui::Key::Alpha4,
+        VirtualKeyCode::Key5 => imgui::Key::Alpha5,
+        VirtualKeyCode::Key6 => imgui::Key::Alpha6,
+        VirtualKeyCode::Key7 => imgui::Key::Alpha7,
+        VirtualKeyCode::Key8 => imgui::Key::Alpha8,
+        VirtualKeyCode::Key9 => imgui::Key::Alpha9,
+        VirtualKeyCode::A => imgui::Key::A,
+        VirtualKeyCode::B => imgui::Key::B,
+        VirtualKeyCode::C => imgui::Key::C,
+        VirtualKeyCode::D => imgui::Key::D,
+        VirtualKeyCode::E => imgui::Key::E,
+        VirtualKeyCode::F => imgui::Key::F,
+        VirtualKeyCode::G => imgui::Key::G,
+        VirtualKeyCode::H => imgui::Key::H,
+        VirtualKeyCode::I => imgui::Key::I,
+        VirtualKeyCode::J => imgui::Key::J,
+        VirtualKeyCode::K => imgui::Key::K,
+        VirtualKeyCode::L => imgui::Key::L,
+        VirtualKeyCode::M => imgui::Key::M,
+        VirtualKeyCode::N => imgui::Key::N,
+        VirtualKeyCode::O => imgui::Key::O,
+        VirtualKeyCode::P => imgui::Key::P,
+        VirtualKeyCode::Q => imgui::Key::Q,
+        VirtualKeyCode::R => imgui::Key::R,
+        VirtualKeyCode::S => imgui::Key::S,
+        VirtualKeyCode::T => imgui::Key::T,
+        VirtualKeyCode::U => imgui::Key::U,
+        VirtualKeyCode::V => imgui::Key::V,
+        VirtualKeyCode::W => imgui::Key::W,
+        VirtualKeyCode::X => imgui::Key::X,
+        VirtualKeyCode::Y => imgui::Key::Y,
+        VirtualKeyCode::Z => imgui::Key::Z,
+        VirtualKeyCode::F1 => imgui::Key::F1,
+        VirtualKeyCode::F2 => imgui::Key::F2,
+        VirtualKeyCode::F3 => imgui::Key::F3,
+        VirtualKeyCode::F4 => imgui::Key::F4,
+        VirtualKeyCode::F5 => imgui::Key::F5,
+        VirtualKeyCode::F6 => imgui::Key::F6,
+        VirtualKeyCode::F7 => imgui::Key::F7,
+        VirtualKeyCode::F8 => imgui::Key::F8,
+        VirtualKeyCode::F9 => imgui::Key::F9,
+        VirtualKeyCode::F10 => imgui::Key::F10,
+        VirtualKeyCode::F11 => imgui::Key::F11,
+        VirtualKeyCode::F12 => imgui::Key::F12,
+        VirtualKeyCode::Apostrophe => imgui::Key::Apostrophe,
+        VirtualKeyCode::Comma => imgui::Key::Comma,
+        VirtualKeyCode::Minus => imgui::Key::Minus,
+        VirtualKeyCode::Period => imgui::Key::Period,
+        VirtualKeyCode::Slash => imgui::Key::Slash,
+        VirtualKeyCode::Semicolon => imgui::Key::Semicolon,
+        VirtualKeyCode::Equals => imgui::Key::Equal,
+        VirtualKeyCode::LBracket => imgui::Key::LeftBracket,
+        VirtualKeyCode::Backslash => imgui::Key::Backslash,
+        VirtualKeyCode::RBracket => imgui::Key::RightBracket,
+        VirtualKeyCode::Grave => imgui::Key::GraveAccent,
+        VirtualKeyCode::Capital => imgui::Key::CapsLock,
+        VirtualKeyCode::Scroll => imgui::Key::ScrollLock,
+        VirtualKeyCode::Numlock => imgui::Key::NumLock,
+        VirtualKeyCode::Snapshot => imgui::Key::PrintScreen,
+        VirtualKeyCode::Pause => imgui::Key::Pause,
+        VirtualKeyCode::Numpad0 => imgui::Key::Keypad0,
+        VirtualKeyCode::Numpad1 => imgui::Key::Keypad1,
+        VirtualKeyCode::Numpad2 => imgui::Key::Keypad2,
+        VirtualKeyCode::Numpad3 => imgui::Key::Keypad3,
+        VirtualKeyCode::Numpad4 => imgui::Key::Keypad4,
+        VirtualKeyCode::Numpad5 => imgui::Key::Keypad5,
+        VirtualKeyCode::Numpad6 => imgui::Key::Keypad6,
+        VirtualKeyCode::Numpad7 => imgui::Key::Keypad7,
+        VirtualKeyCode::Numpad8 => imgui::Key::Keypad8,
+        VirtualKeyCode::Numpad9 => imgui::Key::Keypad9,
+        VirtualKeyCode::NumpadDecimal => imgui::Key::KeypadDecimal,
+        VirtualKeyCode::NumpadDivide => imgui::Key::KeypadDivide,
+        VirtualKeyCode::NumpadMultiply => imgui::Key::KeypadMultiply,
+        VirtualKeyCode::NumpadSubtract => imgui::Key::KeypadSubtract,
+        VirtualKeyCode::NumpadAdd => imgui::Key::KeypadAdd,
+        VirtualKeyCode::NumpadEnter => imgui::Key::KeypadEnter,
+        VirtualKeyCode::NumpadEquals => imgui::Key::KeypadEqual,
+        _ => return None
+    })
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(id: u64, phase: TouchPhase, x: f32, y: f32) -> TouchPoint {
+        TouchPoint {
+            id,
+            phase,
+            position: Vector2::new(x, y),
+            force: None
+        }
+    }
+
+    fn mouse(x: f32, y: f32, button: Option<bool>) -> Option<TouchMouse> {
+        Some(TouchMouse {
+            position: Vector2::new(x, y),
+            button
+        })
+    }
+
+    #[test]
+    fn touch_phases() {
+        let mut tracker = TouchTracker::new();
+        tracker.add(touch(1, TouchPhase::Started, 10.0, 20.0));
+        tracker.add(touch(1, TouchPhase::Moved, 11.0, 21.0));
+        assert_eq!(tracker.touches(), &[touch(1, TouchPhase::Started, 11.0, 21.0)]);
+
+        tracker.update();
+        assert_eq!(tracker.touches(), &[touch(1, TouchPhase::Moved, 11.0, 21.0)]);
+
+        tracker.update();
+        assert_eq!(tracker.touches(), &[touch(1, TouchPhase::Moved, 11.0, 21.0)]);
+
+        tracker.add(touch(1, TouchPhase::Ended, 12.0, 22.0));
+        assert_eq!(tracker.touches(), &[touch(1, TouchPhase::Ended, 12.0, 22.0)]);
+
+        tracker.update();
+        assert!(tracker.touches().is_empty());
+    }
+
+    #[test]
+    fn tap_within_one_frame() {
+        let mut tracker = TouchTracker::new();
+        tracker.add(touch(1, TouchPhase::Started, 5.0, 5.0));
+        tracker.add(touch(1, TouchPhase::Ended, 5.0, 5.0));
+        assert_eq!(tracker.touches(), &[touch(1, TouchPhase::Ended, 5.0, 5.0)]);
+
+        tracker.update();
+        assert!(tracker.touches().is_empty());
+    }
+
+    #[test]
+    fn multiple_touches() {
+        let mut tracker = TouchTracker::new();
+        tracker.add(touch(1, TouchPhase::Started, 0.0, 0.0));
+        tracker.add(touch(2, TouchPhase::Started, 50.0, 50.0));
+        tracker.update();
+
+        tracker.add(touch(2, TouchPhase::Cancelled, 50.0, 50.0));
+        assert_eq!(tracker.touches(), &[touch(1, TouchPhase::Moved, 0.0, 0.0), touch(2, TouchPhase::Cancelled, 50.0, 50.0)]);
+
+        tracker.update();
+        assert_eq!(tracker.touches(), &[touch(1, TouchPhase::Moved, 0.0, 0.0)]);
+    }
+
+    #[test]
+    fn primary_touch_drives_mouse() {
+        let mut tracker = TouchTracker::new();
+        assert_eq!(tracker.add(touch(1, TouchPhase::Started, 10.0, 20.0)), mouse(10.0, 20.0, Some(true)));
+        tracker.update();
+
+        assert_eq!(tracker.add(touch(1, TouchPhase::Moved, 15.0, 25.0)), mouse(15.0, 25.0, None));
+        tracker.update();
+
+        assert_eq!(tracker.add(touch(1, TouchPhase::Ended, 16.0, 26.0)), mouse(16.0, 26.0, Some(false)));
+        tracker.update();
+
+        assert_eq!(tracker.add(touch(2, TouchPhase::Started, 30.0, 40.0)), mouse(30.0, 40.0, Some(true)));
+        assert_eq!(tracker.add(touch(2, TouchPhase::Cancelled, 30.0, 40.0)), mouse(30.0, 40.0, Some(false)));
+    }
+
+    #[test]
+    fn secondary_touches_leave_mouse_alone() {
+        let mut tracker = TouchTracker::new();
+        assert_eq!(tracker.add(touch(1, TouchPhase::Started, 0.0, 0.0)), mouse(0.0, 0.0, Some(true)));
+        assert_eq!(tracker.add(touch(2, TouchPhase::Started, 50.0, 50.0)), None);
+        tracker.update();
+
+        assert_eq!(tracker.add(touch(2, TouchPhase::Moved, 60.0, 60.0)), None);
+        assert_eq!(tracker.add(touch(1, TouchPhase::Ended, 1.0, 1.0)), mouse(1.0, 1.0, Some(false)));
+        tracker.update();
+
+        // A finger that is already down doesn't take over when the primary touch lifts.
+        assert_eq!(tracker.add(touch(2, TouchPhase::Moved, 70.0, 70.0)), None);
+        assert_eq!(tracker.add(touch(3, TouchPhase::Started, 80.0, 80.0)), mouse(80.0, 80.0, Some(true)));
+    }
+
+    #[test]
+    fn mouse_button_slots() {
+        assert_eq!(Input::mb_to_idx(MouseButton::Right), Some(0));
+        assert_eq!(Input::mb_to_idx(MouseButton::Left), Some(2));
+        assert_eq!(Input::mb_to_idx(MouseButton::Other(0)), Some(3));
+        assert_eq!(Input::mb_to_idx(MouseButton::Other(28)), Some(MAX_BUTTONS - 1));
+        assert_eq!(Input::mb_to_idx(MouseButton::Other(29)), None);
+        assert_eq!(Input::mb_to_idx(MouseButton::Other(u16::MAX)), None);
+    }
+
+    #[test]
+    fn event_order_and_timestamps() {
+        let mut queue = EventQueue::new();
+        let before = Instant::now();
+        queue.push(key_event(VirtualKeyCode::A, true, false));
+        queue.push(InputEventKind::MouseMove { position: Vector2::new(3, 4) });
+        queue.push(InputEventKind::Text("a".to_string()));
+        queue.push(key_event(VirtualKeyCode::A, false, true));
+        let after = Instant::now();
+
+        let kinds: Vec<_> = queue.events().iter().map(|event| event.kind.clone()).collect();
+        assert_eq!(kinds, vec![
+            InputEventKind::Key { key_code: VirtualKeyCode::A, pressed: true, repeat: false },
+            InputEventKind::MouseMove { position: Vector2::new(3, 4) },
+            InputEventKind::Text("a".to_string()),
+            InputEventKind::Key { key_code: VirtualKeyCode::A, pressed: false, repeat: false }
+        ]);
+
+        let times: Vec<_> = queue.events().iter().map(|event| event.time).collect();
+        assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(before <= times[0] && times[times.len() - 1] <= after);
+
+        queue.clear();
+        assert!(queue.events().is_empty());
+    }
+
+    #[test]
+    fn key_repeat() {
+        assert_eq!(key_event(VirtualKeyCode::W, true, false), InputEventKind::Key { key_code: VirtualKeyCode::W, pressed: true, repeat: false });
+        assert_eq!(key_event(VirtualKeyCode::W, true, true), InputEventKind::Key { key_code: VirtualKeyCode::W, pressed: true, repeat: true });
+        assert_eq!(key_event(VirtualKeyCode::W, false, true), InputEventKind::Key { key_code: VirtualKeyCode::W, pressed: false, repeat: false });
+        assert_eq!(key_event(VirtualKeyCode::W, false, false), InputEventKind::Key { key_code: VirtualKeyCode::W, pressed: false, repeat: false });
+    }
+
+    #[test]
+    fn text_filtering() {
+        assert_eq!(text_character('a'), Some('a'));
+        assert_eq!(text_character('\r'), Some('\n'));
+        assert_eq!(text_character('\t'), Some('\t'));
+        assert_eq!(text_character('\u{8}'), None);
+        assert_eq!(text_character('\u{1b}'), None);
+        assert_eq!(text_character('\u{7f}'), None);
+        assert_eq!(filter_text("ä\u{8}b\r\u{13}日本"), "äb\n日本");
+        assert_eq!(filter_text("\u{1b}"), "");
+    }
+}
@@ -16,19 +16,19 @@
 //! minigw = "0.0.6"
 //! ```
 //! This example shows how to create a window and how to draw a gradient every frame.
-//! ```rust
+//! ```rust,no_run
 //! extern crate minigw;
 //! 
 //! fn main() {
 //!     minigw::new::<u8, _>("Example", 1280, 720,
-//!         move |input, render_texture, imgui| {  
+//!         move |_window, _input, render_texture, _imgui| {
 //!             let mut render_texture = render_texture.as_mut();
 //! 
 //!             // Draw a red and green gradient.
-//!             for x in 0..render_texture.width() {
-//!                 for y in 0..render_texture.height() {
-//!                     let uv = (x as f32 / render_texture.width() as f32, y as f32 / render_texture.height() as f32);
-//!                     render_texture.set_pixel(x, y, &[(uv.0 * 255.99) as u8, (uv.1 * 255.99) as u8, 0]);
+//!             for x in 0..render_texture.get_width() {
+//!                 for y in 0..render_texture.get_height() {
+//!                     let uv = (x as f32 / render_texture.get_width() as f32, y as f32 / render_texture.get_height() as f32);
+//!                     render_texture.set_pixel(x, y, (uv.0 * 255.99) as u8, (uv.1 * 255.99) as u8, 0);
 //!                 }
 //!             }
 //!         });
@@ -50,13 +50,13 @@
 //! # Examples
 //! ## Input Handling & ImGui
 //! Input can be handled by querying the state of the input struct. This example will toggle the cursor state between unlocked and locked every time the Space Bar has been pressed. Debug UI can be drawn by directly accessing the `imgui::Ui` struct.
-//! ```rust
+//! ```rust,no_run
 //! extern crate minigw;
 //! use minigw::imgui;
 //! 
 //! fn main() {
 //!     minigw::new::<u8, _>("Example", 1280, 720,
-//!         move |input, render_texture, imgui| {
+//!         move |_window, input, _render_texture, imgui| {
 //!             // ...
 //! 
 //!             // Toggle the cursor mode between FREE and LOCKED.
@@ -77,22 +77,28 @@
 //! 
 //! ## Framebuffer type
 //! The framebuffer type can be defined when creating a new window. `u8` works best for performance reasons but other supported types are: `i8`, `u16`, `i16`, `u32`, `i32` and `f32`. The same gradient example but now with a `f32` framebuffer can be seen below.
-//! ```rust
+//! ```rust,no_run
 //! extern crate minigw;
 //! 
 //! fn main() {
 //!     minigw::new::<f32, _>("Example", 1280, 720,
-//!         move |input, render_texture, imgui| {  
+//!         move |_window, _input, render_texture, _imgui| {
+//!             let mut render_texture = render_texture.as_mut();
+//! 
 //!             // ...
-//!                 // ...
-//!                     render_texture.set_pixel(x, y, &[uv.0, uv.1, 0]);
+//!             for x in 0..render_texture.get_width() {
+//!                 for y in 0..render_texture.get_height() {
+//!                     let uv = (x as f32 / render_texture.get_width() as f32, y as f32 / render_texture.get_height() as f32);
+//!                     render_texture.set_pixel(x, y, uv.0, uv.1, 0.0);
+//!                 }
+//!             }
 //!         });
 //! }
 //! ```
 //! 
 //! ## Window & Framebuffer resizing
 //! The resizing of the window can be enabled and disabled. The framebuffer's rendertexture can: resize with the window, resize with the window scaled by a factor or don't resize with the window. The example below shows how to switch between these modes.
-//! ```rust
+//! ```rust,no_run
 //! extern crate minigw;
 //! 
 //! fn main() {
@@ -108,9 +114,9 @@
 //!         for x in 0..render_texture.get_width() {
 //!             for y in 0..render_texture.get_height() {
 //!                 if (x / 60 + y / 60) % 2 == 0 {
-//!                     render_texture.set_pixel(x, y, &[255, 255, 255]);
+//!                     render_texture.set_pixel(x, y, 255, 255, 255);
 //!                 } else {
-//!                     render_texture.set_pixel(x, y, &[0, 0, 0]);
+//!                     render_texture.set_pixel(x, y, 0, 0, 0);
 //!                 }
 //!             }
 //!         }
@@ -135,32 +141,35 @@
 //!     });
 //! }
 //! ```
-//! ## Adding a window & taskbar icon
-//! Adding a window icon requires a `Vec<u8>` of pixel data. To get the data this example uses the [stb_image crate](https://crates.io/crates/stb_image) but feel free to use any image library that suits your needs best.
-//! ```rust
+//! ## Window configuration
+//! Creation settings such as vsync can be passed with a `WindowConfig`. The swap interval can also be changed at runtime.
+//! ```rust,no_run
 //! extern crate minigw;
-//! extern crate stb_image;
-//! 
-//! fn load_img(path: &str) -> (Vec<u8>, u32, u32) {
-//!     let cpath = std::ffi::CString::new(path.as_bytes()).unwrap();
-//! 
-//!     unsafe {
-//!         let mut width = 0;
-//!         let mut height = 0;
-//!         let mut channels = 0;
-//!         let data = stb_image::stb_image::bindgen::stbi_load(
-//!             cpath.as_ptr(),
-//!             &mut width,
-//!             &mut height,
-//!             &mut channels,
-//!             4
-//!         );
-//!         assert!(!data.is_null(), "Failed to read image file at \"{:?}\"", path);
-//!         let data: Vec<u8> = std::slice::from_raw_parts(data, (width * height * 4) as usize).to_vec();
-//! 
-//!         (data, width as u32, height as u32)
-//!     }
+//! 
+//! fn main() {
+//!     let config = minigw::WindowConfig {
+//!         vsync: false,
+//!         ..minigw::WindowConfig::new("Example", 1280, 720)
+//!     };
+//! 
+//!     minigw::new_with_config::<u8, _>(config,
+//!     move |window, input, _render_texture, _imgui| {
+//!         // Toggle vsync.
+//!         if input.as_ref().key_down(minigw::VirtualKeyCode::V) {
+//!             let mut window_mut = window.as_mut();
+//!             let swap_interval = match window_mut.get_swap_interval() {
+//!                 minigw::SwapInterval::Off => minigw::SwapInterval::On,
+//!                 _ => minigw::SwapInterval::Off
+//!             };
+//!             window_mut.set_swap_interval(swap_interval);
+//!         }
+//!     });
 //! }
+//! ```
+//! ## Adding a window & taskbar icon
+//! A window icon can be loaded straight from a PNG, ICO or BMP file. For a multi-resolution ICO the image that fits the window and taskbar best is picked.
+//! ```rust,no_run
+//! extern crate minigw;
 //! 
 //! fn main() {
 //!     let mut once = true;
@@ -170,9 +179,8 @@
 //!         if once {
 //!             once = false;
 //! 
-//!             let (rgba, width, height) = load_img("assets/rust.png");
-//!             let icon = minigw::window::Icon::from_rgba(rgba, width, height).unwrap();
-//!             window.as_mut().set_icon(Some(icon));
+//!             window.as_ref().set_icon_from_path("assets/rust.ico")
+//!                 .expect("Failed to load window icon.");
 //!         }
 //!     });
 //! }
@@ -189,28 +197,59 @@ pub use rc_cell::*;
 
 pub mod input;
 pub use input::*;
+pub mod shortcut;
+pub use shortcut::*;
+pub mod action_map;
+pub use action_map::*;
+pub mod gamepad;
+pub use gamepad::*;
 pub mod renderer;
 pub use renderer::*;
 pub mod window;
 pub use window::*;
+pub mod cursor;
+pub use cursor::*;
+pub mod decode;
+pub use decode::{ImageLoadError, IconExt};
+pub mod gl_config;
+pub use gl_config::*;
+pub mod capture;
+pub use capture::Capture;
+use capture::{CaptureTarget, hotkey_capture_path};
 
+mod clipboard;
+pub use clipboard::ClipboardError;
+use clipboard::*;
 mod core_loop;
 use core_loop::*;
+mod persistence;
+use persistence::*;
 mod gl_helpers;
 use gl_helpers::DebugUI;
 
 /// Creates a new minigw game window.
 pub fn new<T, F>(
-    title: &'static str,
+    title: impl Into<String>,
     width: u32,
     height: u32,
     core_update: F
 ) where
     T: RenderTextureType + 'static,
     F: FnMut(RcCell<Window>, RcCell<Input>, RcCell<RenderTexture<T>>, &mut DebugUI) + 'static
+{
+    new_with_config(WindowConfig::new(title, width, height), core_update);
+}
+
+/// Creates a new minigw game window with custom creation settings.
+pub fn new_with_config<T, F>(
+    config: WindowConfig,
+    core_update: F
+) where
+    T: RenderTextureType + 'static,
+    F: FnMut(RcCell<Window>, RcCell<Input>, RcCell<RenderTexture<T>>, &mut DebugUI) + 'static
 {
     let core_loop = CoreLoop::new();
-    let window = Window::new(&core_loop, title, width, height);
+    let window = Window::new(&core_loop, &config);
     let input = Input::new(window.clone());
 
     core_loop.run(
@@ -0,0 +1,291 @@
+use std::path::{Component, Path, PathBuf};
+
+use cgmath::Vector2;
+
+use crate::RenderTextureResizing;
+
+/// Larger window sizes in a settings file are treated as corrupted.
+const MAX_SIZE: u32 = 1 << 16;
+
+/// Window geometry that is persisted between runs in a small `key = value` settings file.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct WindowState {
+    /// Outer position in physical pixels.
+    pub position: Option<Vector2<i32>>,
+    /// Inner size in physical pixels.
+    pub size: Vector2<u32>,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    pub resizing: Option<RenderTextureResizing>
+}
+
+impl WindowState {
+    /// Load the persisted state of an app, `None` if there is no (valid) settings file yet or its size is zero or absurdly large.
+    pub(crate) fn load(app_name: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(settings_path(app_name)?).ok()?;
+        Self::parse(&contents)
+    }
+
+    /// Save the state of an app, failures are reported but not fatal.
+    pub(crate) fn save(&self, app_name: &str) {
+        let path = match settings_path(app_name) {
+            Some(path) => path,
+            None => {
+                eprintln!("Failed to save window state, no config directory found or invalid app name {:?}.", app_name);
+                return;
+            }
+        };
+
+        let result = path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, self.serialize()));
+        if let Err(e) = result {
+            eprintln!("Failed to save window state to {:?}: {}", path, e);
+        }
+    }
+
+    fn parse(contents: &str) -> Option<Self> {
+        let (mut x, mut y, mut width, mut height) = (None, None, None, None);
+        let mut maximized = false;
+        let mut fullscreen = false;
+        let mut resizing = None;
+
+        for line in contents.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => continue
+            };
+
+            match key {
+                "x" => x = value.parse().ok(),
+                "y" => y = value.parse().ok(),
+                "width" => width = value.parse().ok(),
+                "height" => height = value.parse().ok(),
+                "maximized" => maximized = value == "true",
+                "fullscreen" => fullscreen = value == "true",
+                "resizing" => resizing = parse_resizing(value),
+                _ => {}
+            }
+        }
+
+        let size = Vector2::new(width?, height?);
+        if !(1..=MAX_SIZE).contains(&size.x) || !(1..=MAX_SIZE).contains(&size.y) {
+            return None;
+        }
+
+        Some(WindowState {
+            position: x.zip(y).map(|(x, y)| Vector2::new(x, y)),
+            size,
+            maximized,
+            fullscreen,
+            resizing
+        })
+    }
+
+    fn serialize(&self) -> String {
+        let mut contents = String::new();
+        if let Some(position) = self.position {
+            contents += &format!("x = {}\ny = {}\n", position.x, position.y);
+        }
+        contents += &format!("width = {}\nheight = {}\n", self.size.x, self.size.y);
+        contents += &format!("maximized = {}\nfullscreen = {}\n", self.maximized, self.fullscreen);
+        if let Some(resizing) = self.resizing {
+            contents += &format!("resizing = {}\n", serialize_resizing(resizing));
+        }
+        contents
+    }
+
+    /// Make sure the window is visible on the monitors that are currently connected, given as (position, size) rectangles in physical pixels.
+    /// The size is clamped to the monitor the window is on, a window that is off-screen is centered on the first monitor.
+    pub(crate) fn clamp_to_monitors(&mut self, monitors: &[(Vector2<i32>, Vector2<u32>)]) {
+        let position = match self.position {
+            Some(position) => position,
+            None => return
+        };
+
+        // The position comes from a file that may have been edited by hand, so it can be anywhere in the i32 range.
+        let center = Vector2::new(position.x.saturating_add((self.size.x / 2) as i32), position.y.saturating_add((self.size.y / 2) as i32));
+        let contains_center = |&&(min, size): &&(Vector2<i32>, Vector2<u32>)| {
+            center.x >= min.x && center.x < min.x.saturating_add(size.x as i32) &&
+            center.y >= min.y && center.y < min.y.saturating_add(size.y as i32)
+        };
+
+        let (&(min, size), on_screen) = match monitors.iter().find(contains_center) {
+            Some(monitor) => (monitor, true),
+            None => match monitors.first() {
+                Some(monitor) => (monitor, false),
+                None => return
+            }
+        };
+
+        self.size = Vector2::new(self.size.x.min(size.x), self.size.y.min(size.y));
+
+        let max = Vector2::new(min.x + (size.x - self.size.x) as i32, min.y + (size.y - self.size.y) as i32);
+        self.position = Some(if on_screen {
+            Vector2::new(position.x.clamp(min.x, max.x), position.y.clamp(min.y, max.y))
+        } else {
+            Vector2::new((min.x + max.x) / 2, (min.y + max.y) / 2)
+        });
+    }
+}
+
+fn parse_resizing(value: &str) -> Option<RenderTextureResizing> {
+    match value.split_once(' ') {
+        Some(("ResizableScaled", scale)) => scale.trim().parse().ok().map(RenderTextureResizing::ResizableScaled),
+        _ => match value {
+            "Resizable" => Some(RenderTextureResizing::Resizable),
+            "ResizableLogical" => Some(RenderTextureResizing::ResizableLogical),
+            "NonResizable" => Some(RenderTextureResizing::NonResizable),
+            _ => None
+        }
+    }
+}
+
+fn serialize_resizing(resizing: RenderTextureResizing) -> String {
+    match resizing {
+        RenderTextureResizing::Resizable => "Resizable".to_owned(),
+        RenderTextureResizing::ResizableLogical => "ResizableLogical".to_owned(),
+        RenderTextureResizing::ResizableScaled(scale) => format!("ResizableScaled {}", scale),
+        RenderTextureResizing::NonResizable => "NonResizable".to_owned()
+    }
+}
+
+/// The settings file of an app in the per-user config directory, `None` if the app name isn't a valid directory name.
+fn settings_path(app_name: &str) -> Option<PathBuf> {
+    if !is_valid_app_name(app_name) {
+        return None;
+    }
+
+    let config_dir = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+
+    Some(config_dir?.join(app_name).join("minigw_window.cfg"))
+}
+
+/// Check that an app name is a single directory name, so the settings file can't end up outside of the config directory.
+fn is_valid_app_name(app_name: &str) -> bool {
+    let mut components = Path::new(app_name).components();
+    matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none() &&
+        !app_name.contains(['/', '\\', ':'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITORS: [(Vector2<i32>, Vector2<u32>); 2] = [
+        (Vector2::new(0, 0), Vector2::new(1920, 1080)),
+        (Vector2::new(1920, 0), Vector2::new(1280, 1024))
+    ];
+
+    fn state(position: Option<(i32, i32)>, width: u32, height: u32) -> WindowState {
+        WindowState {
+            position: position.map(|(x, y)| Vector2::new(x, y)),
+            size: Vector2::new(width, height),
+            maximized: false,
+            fullscreen: false,
+            resizing: None
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut saved = state(Some((-20, 40)), 800, 600);
+        saved.maximized = true;
+        for resizing in [None, Some(RenderTextureResizing::Resizable), Some(RenderTextureResizing::ResizableLogical), Some(RenderTextureResizing::ResizableScaled(2.5)), Some(RenderTextureResizing::NonResizable)] {
+            saved.resizing = resizing;
+            assert_eq!(WindowState::parse(&saved.serialize()), Some(saved.clone()));
+        }
+
+        let mut saved = state(None, 1, 1);
+        saved.fullscreen = true;
+        assert_eq!(WindowState::parse(&saved.serialize()), Some(saved));
+    }
+
+    #[test]
+    fn malformed_files() {
+        assert_eq!(WindowState::parse(""), None);
+        assert_eq!(WindowState::parse("width = 800"), None);
+        assert_eq!(WindowState::parse("width = 800\nheight = tall"), None);
+        assert_eq!(WindowState::parse("width = -800\nheight = 600"), None);
+        assert_eq!(WindowState::parse("width = 0\nheight = 600"), None);
+        assert_eq!(WindowState::parse("width = 800\nheight = 0"), None);
+        assert_eq!(WindowState::parse("width = 4000000000\nheight = 600"), None);
+
+        // Unknown keys, lines without `=` and a half position are skipped.
+        let parsed = WindowState::parse("garbage\nx = 10\ncolor = red\nwidth = 800\nheight = 600\nresizing = Stretchy");
+        assert_eq!(parsed, Some(state(None, 800, 600)));
+    }
+
+    #[test]
+    fn resizing_values() {
+        assert_eq!(parse_resizing("Resizable"), Some(RenderTextureResizing::Resizable));
+        assert_eq!(parse_resizing("ResizableScaled 0.5"), Some(RenderTextureResizing::ResizableScaled(0.5)));
+        assert_eq!(parse_resizing("ResizableScaled  3 "), Some(RenderTextureResizing::ResizableScaled(3.0)));
+        assert_eq!(parse_resizing("ResizableScaled"), None);
+        assert_eq!(parse_resizing("ResizableScaled big"), None);
+        assert_eq!(parse_resizing("resizable"), None);
+    }
+
+    #[test]
+    fn clamp_on_screen() {
+        let mut restored = state(Some((100, 100)), 800, 600);
+        restored.clamp_to_monitors(&MONITORS);
+        assert_eq!(restored, state(Some((100, 100)), 800, 600));
+
+        // Hanging off the right edge of the second monitor.
+        let mut restored = state(Some((2600, 100)), 800, 600);
+        restored.clamp_to_monitors(&MONITORS);
+        assert_eq!(restored, state(Some((2400, 100)), 800, 600));
+    }
+
+    #[test]
+    fn clamp_off_screen() {
+        let mut restored = state(Some((-5000, 3000)), 800, 600);
+        restored.clamp_to_monitors(&MONITORS);
+        assert_eq!(restored, state(Some((560, 240)), 800, 600));
+
+        let mut restored = state(Some((i32::MAX, i32::MIN)), 800, 600);
+        restored.clamp_to_monitors(&MONITORS);
+        assert_eq!(restored, state(Some((560, 240)), 800, 600));
+    }
+
+    #[test]
+    fn clamp_oversized() {
+        let mut restored = state(Some((1000, -1000)), 4000, 3000);
+        restored.clamp_to_monitors(&MONITORS);
+        assert_eq!(restored, state(Some((1920, 0)), 1280, 1024));
+    }
+
+    #[test]
+    fn clamp_without_monitors() {
+        let mut restored = state(Some((-5000, 3000)), 800, 600);
+        restored.clamp_to_monitors(&[]);
+        assert_eq!(restored, state(Some((-5000, 3000)), 800, 600));
+
+        let mut restored = state(None, 800, 600);
+        restored.clamp_to_monitors(&MONITORS);
+        assert_eq!(restored, state(None, 800, 600));
+    }
+
+    #[test]
+    fn app_names() {
+        assert!(is_valid_app_name("my_game"));
+        assert!(is_valid_app_name("My Game 2"));
+        assert!(!is_valid_app_name(""));
+        assert!(!is_valid_app_name("."));
+        assert!(!is_valid_app_name(".."));
+        assert!(!is_valid_app_name("../../x"));
+        assert!(!is_valid_app_name("/etc"));
+        assert!(!is_valid_app_name("a/b"));
+        assert!(!is_valid_app_name("a\\b"));
+        assert!(!is_valid_app_name("C:x"));
+    }
+}
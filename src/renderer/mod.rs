@@ -1,7 +1,9 @@
-use cgmath::Vector3;
+use cgmath::{Vector2, Vector3, Vector4};
 
 use crate::RcCell;
 use crate::Window;
+use crate::{Input, CursorMode};
+use crate::{Capture, CaptureTarget};
 use crate::gl_helpers::*;
 
 mod shaders;
@@ -10,35 +12,52 @@ pub mod render_texture;
 pub use render_texture::*;
 pub mod render_texture_type;
 pub use render_texture_type::*;
+pub mod viewport;
+pub use viewport::*;
 
 pub(crate) struct Renderer<T: RenderTextureType> {
     imgui: ImGui,
     display_program: GLShaderProgram,
-    display_vao: GLVAO,
+    quad_vao: Option<GLVAO>,
+    quad_vbo: GLVBO,
+    cursor_program: GLShaderProgram,
+    cursor_texture: Option<GLTexture>,
+    cursor_image_generation: Option<u64>,
     render_textures: [RcCell<RenderTexture<T>>; 2],
     render_texture_idx: usize,
-    prev_render_texture_idx: usize
+    prev_render_texture_idx: usize,
+    scale_factor: f64,
+    window_size: Vector2<u32>,
+    viewport: Viewport,
+    suspended: bool,
+    context_lost: bool,
+    capture_request: Option<CaptureTarget>,
+    capture_readback: Option<(GLPBO, u32, u32, CaptureTarget)>,
+    capture: Option<(Capture, CaptureTarget)>,
+    cursor_opaque: Option<bool>
 }
 
 impl<T: RenderTextureType> Renderer<T> {
     pub(crate) fn new(window: &Window) -> Renderer<T> {
-        gl_init(window);
+        let (width, height) = (window.get_physical_width(), window.get_physical_height());
+        let scale_factor = window.get_scale_factor();
 
-        let (width, height) = (window.get_width(), window.get_height());
+        let capabilities = window.gl_capabilities();
 
-        let mut imgui = ImGui::new();
-        imgui.resize(width, height);
+        let mut imgui = ImGui::new(window.clipboard(), capabilities);
+        imgui.resize(width, height, scale_factor);
         gl_viewport(width, height);
 
-        let vertex_shader = GLShader::new(GLShaderType::Vertex, DISPLAY_SHADER_SRC_VERT);
-        let fragment_shader = GLShader::new(GLShaderType::Fragment, DISPLAY_SHADER_SRC_FRAG);
-        let display_program = GLShaderProgram::new(&vertex_shader, &fragment_shader);
-        let display_vao = GLVAO::new();
+        let (display_program, cursor_program, quad_vao, quad_vbo) = Self::create_pipeline(capabilities);
+
+        let window_size = Vector2::new(width.max(1), height.max(1));
+        let viewport = window.get_viewport();
+        let (_, viewport_size) = viewport.rect(window_size);
 
-        let use_pbo = window.support_pbo();
+        let alpha = window.is_transparent();
         let render_textures = [
-            RcCell::new(RenderTexture::new(width, height, use_pbo, RenderTextureResizing::Resizable)),
-            RcCell::new(RenderTexture::new(width, height, use_pbo, RenderTextureResizing::Resizable))
+            RcCell::new(RenderTexture::new(viewport_size.x, viewport_size.y, scale_factor, capabilities, alpha, RenderTextureResizing::Resizable)),
+            RcCell::new(RenderTexture::new(viewport_size.x, viewport_size.y, scale_factor, capabilities, alpha, RenderTextureResizing::Resizable))
         ];
         let prev_render_texture_idx = render_textures.len() - 1;
 
@@ -46,25 +65,152 @@ impl<T: RenderTextureType> Renderer<T> {
             imgui,
             display_program,
             render_textures,
-            display_vao,
+            quad_vao,
+            quad_vbo,
+            cursor_program,
+            cursor_texture: None,
+            cursor_image_generation: None,
             render_texture_idx: 0,
-            prev_render_texture_idx
+            prev_render_texture_idx,
+            scale_factor,
+            window_size,
+            viewport,
+            suspended: false,
+            context_lost: false,
+            capture_request: None,
+            capture_readback: None,
+            capture: None,
+            cursor_opaque: None
+        }
+    }
+
+    fn create_pipeline(capabilities: GLCapabilities) -> (GLShaderProgram, GLShaderProgram, Option<GLVAO>, GLVBO) {
+        let glsl_version = capabilities.glsl_version;
+
+        let vertex_shader = GLShader::new(GLShaderType::Vertex, &glsl_version.vertex_source(DISPLAY_SHADER_SRC_VERT));
+        let fragment_shader = GLShader::new(GLShaderType::Fragment, &glsl_version.fragment_source(DISPLAY_SHADER_SRC_FRAG));
+        let display_program = GLShaderProgram::new(&vertex_shader, &fragment_shader);
+
+        let vertex_shader = GLShader::new(GLShaderType::Vertex, &glsl_version.vertex_source(CURSOR_SHADER_SRC_VERT));
+        let fragment_shader = GLShader::new(GLShaderType::Fragment, &glsl_version.fragment_source(CURSOR_SHADER_SRC_FRAG));
+        let cursor_program = GLShaderProgram::new(&vertex_shader, &fragment_shader);
+
+        // GL 2.x and GLES 2.0 have no VAOs, there the attribute is set up before every draw.
+        let quad_vao = capabilities.vao.then(GLVAO::new);
+        let quad_vbo = GLVBO::new();
+        quad_vbo.bind();
+        quad_vbo.set_data(std::mem::size_of_val(&QUAD_VERTICES), QUAD_VERTICES.as_ptr() as *mut std::ffi::c_void);
+        quad_vbo.unbind();
+
+        (display_program, cursor_program, quad_vao, quad_vbo)
+    }
+
+    /// Recreate every GL object after the context lost them.
+    /// Render textures are restored from their CPU-side pixels and imgui from its font atlas.
+    /// The stale objects are forgotten instead of dropped, their names may already belong to the new objects.
+    pub(crate) fn recreate_gl_resources(&mut self, window: &Window) {
+        let capabilities = window.gl_capabilities();
+
+        let (display_program, cursor_program, quad_vao, quad_vbo) = Self::create_pipeline(capabilities);
+        self.display_program = display_program;
+        self.cursor_program = cursor_program;
+        std::mem::forget(std::mem::replace(&mut self.quad_vao, quad_vao));
+        std::mem::forget(std::mem::replace(&mut self.quad_vbo, quad_vbo));
+
+        self.imgui.recreate_renderer(capabilities);
+        for render_texture in &self.render_textures {
+            render_texture.as_mut().recreate_gl_resources();
+        }
+        std::mem::forget(self.cursor_texture.take());
+        self.cursor_image_generation = None;
+        if let Some((pbo, ..)) = self.capture_readback.take() {
+            std::mem::forget(pbo);
+        }
+
+        gl_viewport(window.get_physical_width().max(1), window.get_physical_height().max(1));
+    }
+
+    /// The application was suspended, on some platforms this destroys the surface and the GL objects with it.
+    pub(crate) fn suspend(&mut self) {
+        self.suspended = true;
+    }
+
+    pub(crate) fn resume(&mut self, window: &Window) {
+        if self.suspended {
+            self.suspended = false;
+            self.recreate_gl_resources(window);
         }
     }
 
+    /// Check if a robust context was reset, returns `false` while nothing should be rendered.
+    /// A reset context stays lost, so once the driver reports the reset is complete the context is rebuilt and every GL object is recreated.
+    /// If the context can't be rebuilt the loss is reported through `Window::is_context_lost` and rendering stops.
+    pub(crate) fn recover_context_loss(&mut self, window: &mut Window) -> bool {
+        if window.is_context_lost() && !self.context_lost {
+            return false;
+        }
+
+        if gl_graphics_reset_status() != gl::NO_ERROR {
+            if !self.context_lost {
+                eprintln!("GL context was reset, rebuilding it once the reset is complete.");
+                window.set_context_lost();
+            }
+            self.context_lost = true;
+            return false;
+        }
+
+        if self.context_lost {
+            self.context_lost = false;
+            if !window.rebuild_context() {
+                eprintln!("Failed to rebuild the GL context after a reset.");
+                return false;
+            }
+            self.recreate_gl_resources(window);
+        }
+        true
+    }
+
     pub(crate) fn imgui(&mut self) -> &mut ImGui {
         &mut self.imgui
     }
 
     pub(crate) fn resize(&mut self, width: u32, height: u32) {
-        self.imgui.resize(width, height);
+        self.imgui.resize(width, height, self.scale_factor);
 
         let width = std::cmp::max(width, 1);
         let height = std::cmp::max(height, 1);
+        self.window_size = Vector2::new(width, height);
 
         gl_viewport(width, height);
+        self.resize_render_textures();
+    }
+
+    /// Apply viewport changes of the window, called before the render texture is drawn to so a resize doesn't clear a finished frame.
+    pub(crate) fn update_viewport(&mut self, window: &Window) {
+        if window.get_viewport() != self.viewport {
+            self.viewport = window.get_viewport();
+            self.resize_render_textures();
+        }
+    }
+
+    fn resize_render_textures(&mut self) {
+        let (_, size) = self.viewport.rect(self.window_size);
+        for render_texture in &self.render_textures {
+            render_texture.as_mut().resize(size.x, size.y);
+        }
+    }
+
+    pub(crate) fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+
         for render_texture in &self.render_textures {
-            render_texture.as_mut().resize(width, height);
+            render_texture.as_mut().set_scale_factor(scale_factor);
+        }
+    }
+
+    pub(crate) fn set_resizing_mode(&mut self, resizing: RenderTextureResizing) {
+        for render_texture in &self.render_textures {
+            render_texture.as_mut().set_resizing_mode(resizing);
         }
     }
 
@@ -72,28 +218,189 @@ impl<T: RenderTextureType> Renderer<T> {
         self.render_textures[self.render_texture_idx].clone()
     }
 
-    pub(crate) fn render(&mut self, window: &Window) {
-        gl_clear_color(Vector3::new(1.0, 0.0, 1.0));
+    /// Read back the default framebuffer at the end of the next `render`.
+    pub(crate) fn request_capture(&mut self, target: CaptureTarget) {
+        self.capture_request = Some(target);
+    }
+
+    /// Take a finished capture and where it should go.
+    pub(crate) fn take_capture(&mut self) -> Option<(Capture, CaptureTarget)> {
+        self.capture.take()
+    }
+
+    /// Take if the pixel under the cursor was opaque in the last frame, `None` if it wasn't sampled.
+    pub(crate) fn take_cursor_opaque(&mut self) -> Option<bool> {
+        self.cursor_opaque.take()
+    }
+
+    pub(crate) fn render(&mut self, window: &Window, input: &Input) {
+        self.finish_capture();
+
+        if window.is_transparent() {
+            gl_clear_color_alpha(Vector4::new(0.0, 0.0, 0.0, 0.0));
+        } else {
+            gl_clear_color(Vector3::new(1.0, 0.0, 1.0));
+        }
         gl_clear();
 
-        let mut render_texture = self.render_textures[self.render_texture_idx].as_mut();
-        render_texture.async_write();
+        self.render_textures[self.render_texture_idx].as_mut().async_write();
 
+        if window.srgb() {
+            gl_framebuffer_srgb(true);
+        }
         self.display_program.bind(); {
             let mut old_render_texture = self.render_textures[self.prev_render_texture_idx].as_mut();
             old_render_texture.flush_write();
             old_render_texture.bind(0);
             self.display_program.set_sampler_slot(&"tex".to_owned(), 0);
 
-            self.display_vao.bind();
-            gl_draw_arrays(gl::TRIANGLES, 0, 3);
+            // GL viewports start at the bottom left.
+            let (position, size) = self.viewport.rect(self.window_size);
+            gl_viewport_rect(position.x, self.window_size.y as i32 - position.y - size.y as i32, size.x, size.y);
+            Self::draw_quad(self.quad_vao.as_ref(), &self.quad_vbo, &self.display_program, gl::TRIANGLES, FULLSCREEN_TRIANGLE_FIRST, 3);
+            gl_viewport(self.window_size.x, self.window_size.y);
         } self.display_program.unbind();
+        if window.srgb() {
+            gl_framebuffer_srgb(false);
+        }
 
         self.imgui.render();
+        self.render_cursor(window, input);
+
+        // Place the IME candidate window below the text cursor of the focused imgui text field.
+        if let Some(ime_data) = self.imgui.take_ime_data().filter(|ime_data| ime_data.visible) {
+            let scale_factor = self.scale_factor as f32;
+            window.set_ime_position(cgmath::Vector2::new(
+                (ime_data.position.x * scale_factor) as i32,
+                ((ime_data.position.y + ime_data.line_height) * scale_factor) as i32
+            ));
+        }
+
+        if let Some(target) = self.capture_request.take() {
+            self.start_capture(window, target);
+        }
+
+        // Sample the composited frame for `ClickThrough::Transparent` before it is swapped away.
+        if let Some(cursor) = window.click_through_cursor_position() {
+            let size = self.window_size;
+            if cursor.x >= 0 && cursor.y >= 0 && (cursor.x as u32) < size.x && (cursor.y as u32) < size.y {
+                self.cursor_opaque = Some(gl_read_pixel_alpha(cursor.x, size.y as i32 - 1 - cursor.y) != 0);
+            }
+        }
+
         window.internal_context().swap_buffers()
             .expect("Failed to swap buffers.");
 
         self.prev_render_texture_idx = self.render_texture_idx;
         self.render_texture_idx = (self.render_texture_idx + 1) % self.render_textures.len();
     }
-}
\ No newline at end of file
+
+    fn render_cursor(&mut self, window: &Window, input: &Input) {
+        if self.cursor_image_generation != Some(window.cursor_image_generation()) {
+            self.cursor_image_generation = Some(window.cursor_image_generation());
+            self.cursor_texture = window.cursor_image().map(|cursor_image| {
+                let texture = GLTexture::new(gl::TEXTURE_2D);
+                texture.bind(); {
+                    gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE);
+                    gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE);
+                    gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST);
+                    gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST);
+
+                    gl_tex_image_2d(gl::RGBA, cursor_image.get_width() as i32, cursor_image.get_height() as i32, gl::RGBA, gl::UNSIGNED_BYTE, cursor_image.rgba().as_ptr() as *const std::ffi::c_void);
+                } texture.unbind();
+                texture
+            });
+        }
+
+        let (cursor_image, cursor_texture) = match (window.cursor_image(), &self.cursor_texture) {
+            (Some(cursor_image), Some(cursor_texture)) => (cursor_image, cursor_texture),
+            _ => return
+        };
+        if !input.mouse_inside() || input.get_cursor_mode() != CursorMode::FREE {
+            return;
+        }
+
+        // Cursor rect in normalized device coordinates: (left, bottom, width, height).
+        let (width, height) = (window.get_physical_width().max(1) as f32, window.get_physical_height().max(1) as f32);
+        let left = (input.mouse_pos().x as f32 - cursor_image.get_hotspot().x as f32) / width;
+        let top = (input.mouse_pos().y as f32 - cursor_image.get_hotspot().y as f32) / height;
+        let rect = Vector4::new(
+            left * 2.0 - 1.0,
+            1.0 - (top + cursor_image.get_height() as f32 / height) * 2.0,
+            cursor_image.get_width() as f32 / width * 2.0,
+            cursor_image.get_height() as f32 / height * 2.0
+        );
+
+        gl_enable_blend();
+        self.cursor_program.bind(); {
+            gl_active_texture(0);
+            cursor_texture.bind();
+            self.cursor_program.set_sampler_slot(&"tex".to_owned(), 0);
+            self.cursor_program.set_float4(&"rect".to_owned(), rect);
+
+            Self::draw_quad(self.quad_vao.as_ref(), &self.quad_vbo, &self.cursor_program, gl::TRIANGLE_STRIP, UNIT_QUAD_FIRST, 4);
+        } self.cursor_program.unbind();
+        gl_disable_blend();
+    }
+
+    /// Read the composited frame before it is swapped, into a pack PBO so the read doesn't stall until the next frame.
+    fn start_capture(&mut self, window: &Window, target: CaptureTarget) {
+        let (width, height) = (window.get_physical_width().max(1), window.get_physical_height().max(1));
+        let size = (width * height * 4) as usize;
+
+        if window.gl_capabilities().pbo {
+            let mut pbo = GLPBO::new_pack();
+            pbo.bind();
+            pbo.allocate(size);
+            gl_read_pixels(width, height, std::ptr::null_mut());
+            pbo.unbind();
+
+            self.capture_readback = Some((pbo, width, height, target));
+        } else {
+            let mut rgba = vec![0u8; size];
+            gl_read_pixels(width, height, rgba.as_mut_ptr() as *mut std::ffi::c_void);
+            self.capture = Some((Capture::new(flip_rows(&rgba, width), width, height), target));
+        }
+    }
+
+    fn finish_capture(&mut self) {
+        if let Some((pbo, width, height, target)) = self.capture_readback.take() {
+            pbo.bind();
+            let pixels: *const u8 = pbo.map();
+            let rgba = unsafe { std::slice::from_raw_parts(pixels, pbo.size()) };
+            let capture = Capture::new(flip_rows(rgba, width), width, height);
+            pbo.unmap();
+            pbo.unbind();
+
+            self.capture = Some((capture, target));
+        }
+    }
+
+    fn draw_quad(vao: Option<&GLVAO>, vbo: &GLVBO, program: &GLShaderProgram, mode: gl::types::GLenum, first: usize, count: usize) {
+        let position = program.attrib_location("position");
+
+        if let Some(vao) = vao {
+            vao.bind();
+        }
+        vbo.bind();
+        gl_enable_vertex_attrib_array(position);
+        gl_vertex_attrib_ptr(position, 2, 0, std::ptr::null());
+
+        gl_draw_arrays(mode, first, count);
+
+        gl_disable_vertex_attrib_array(position);
+        vbo.unbind();
+        if let Some(vao) = vao {
+            vao.unbind();
+        }
+    }
+}
+
+/// GL reads rows bottom to top, captures store them top to bottom.
+fn flip_rows(rgba: &[u8], width: u32) -> Vec<u8> {
+    rgba.chunks_exact((width * 4) as usize)
+        .rev()
+        .flatten()
+        .copied()
+        .collect()
+}
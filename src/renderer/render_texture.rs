@@ -1,17 +1,20 @@
 use super::*;
 
-/// The resizing behaviour of a `RenderTexture`.
-/// - `Resizable` the render texture will be resized to the window size.
-/// - `ResizableScaled(f32)` the render texture will be resized to the window size scaled by a `f32` factor.
-/// - `NonResizable` the render texture will not be resized when the window resizes **or** when `RenderTexture<T>::resize(u32, u32)` is called.
+/// The resizing behaviour of a `RenderTexture`, it follows the viewport which is the whole window by default, see `Window::set_viewport`.
+/// - `Resizable` the render texture will be resized to the viewport size in physical pixels.
+/// - `ResizableLogical` the render texture will be resized to the viewport size in logical pixels.
+/// - `ResizableScaled(f32)` the render texture will be resized to the viewport size in physical pixels scaled by a `f32` factor.
+/// - `NonResizable` the render texture will not be resized when the viewport resizes **or** when `RenderTexture<T>::resize(u32, u32)` is called.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum RenderTextureResizing {
     Resizable,
+    ResizableLogical,
     ResizableScaled(f32),
     NonResizable
 }
 
 /// RenderTexture containing RGB pixel data with every element in the form of `T`.
+/// The render textures of a transparent window also have an alpha channel, see `WindowConfig::transparent`.
 pub struct RenderTexture<T: RenderTextureType> {
     texture: GLTexture,
     pbo: GLPBO,
@@ -20,6 +23,10 @@ pub struct RenderTexture<T: RenderTextureType> {
     width: u32,
     height: u32,
     ty: u32,
+    format: u32,
+    internal_format: u32,
+    channels: u32,
+    scale_factor: f64,
 
     pixels: Vec<T>,
     use_pbo: bool,
@@ -27,47 +34,39 @@ pub struct RenderTexture<T: RenderTextureType> {
 }
 
 impl<T: RenderTextureType> RenderTexture<T> {
-    pub(crate) fn new(width: u32, height: u32, use_pbo: bool, resizing: RenderTextureResizing) -> RenderTexture<T> {
+    pub(crate) fn new(width: u32, height: u32, scale_factor: f64, capabilities: GLCapabilities, alpha: bool, resizing: RenderTextureResizing) -> RenderTexture<T> {
         let src_width = width;
         let src_height = height;
-        let (width, height) = Self::get_sized_dims(width, height, resizing);
+        let (width, height) = Self::get_sized_dims(width, height, scale_factor, resizing);
 
-        gl_pixel_store_i(gl::UNPACK_ALIGNMENT, 1);
-
-        let size = (width * height * std::mem::size_of::<T>() as u32 * 3) as usize;
-
-        let texture = GLTexture::new(gl::TEXTURE_2D);
-        let mut pbo = GLPBO::new();
-        let pixels = vec![T::default(); size / std::mem::size_of::<T>()];
         let ty = T::get_type();
-
-        texture.bind(); {
-            gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE);
-            gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE);
-            gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST);
-            gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST);
-
-            gl_tex_image_2d(gl::RGB, width as i32, height as i32, gl::RGB, ty, std::ptr::null());
-
-            pbo.bind();
-            pbo.allocate(size);
-            pbo.unbind();
-        } texture.unbind();
-
-        gl_pixel_store_i(gl::UNPACK_ALIGNMENT, 4);
-
-        RenderTexture {
-            texture,
-            pbo,
+        let (format, channels) = if alpha { (gl::RGBA, 4) } else { (gl::RGB, 3) };
+        // GLES 3 only accepts float data for a sized float internal format.
+        let internal_format = match (capabilities.gles && ty == gl::FLOAT, alpha) {
+            (true, true) => gl::RGBA32F,
+            (true, false) => gl::RGB32F,
+            (false, _) => format
+        };
+
+        let mut render_texture = RenderTexture {
+            texture: GLTexture::new(gl::TEXTURE_2D),
+            pbo: GLPBO::new(),
             src_width,
             src_height,
             width,
             height,
             ty,
-            pixels,
-            use_pbo,
+            format,
+            internal_format,
+            channels,
+            scale_factor,
+            pixels: vec![T::default(); (width * height * channels) as usize],
+            use_pbo: capabilities.pbo,
             resizing
-        }
+        };
+
+        render_texture.setup_gl_resources();
+        render_texture
     }
 
     pub(crate) fn bind(&self, slot: u32) {
@@ -78,7 +77,7 @@ impl<T: RenderTextureType> RenderTexture<T> {
     pub(crate) fn async_write(&mut self) {
         if self.use_pbo {
             self.pbo.bind();
-            let pixels = self.pbo.map() as *mut T;
+            let pixels: *mut T = self.pbo.map();
             unsafe {
                 pixels.copy_from_nonoverlapping(self.pixels.as_ptr(), self.pixels.len());
             }
@@ -93,18 +92,26 @@ impl<T: RenderTextureType> RenderTexture<T> {
             gl_pixel_store_i(gl::UNPACK_ALIGNMENT, 1);
 
             self.texture.bind();
-            gl_tex_sub_image_2d(self.width as i32, self.height as i32, gl::RGB, self.ty, std::ptr::null());
+            gl_tex_sub_image_2d(self.width as i32, self.height as i32, self.format, self.ty, std::ptr::null());
             self.texture.unbind();
 
             gl_pixel_store_i(gl::UNPACK_ALIGNMENT, 4);
             self.pbo.unbind();
         } else {
             self.texture.bind();
-            gl_tex_sub_image_2d(self.width as i32, self.height as i32, gl::RGB, self.ty, self.pixels.as_ptr() as *const std::ffi::c_void);
+            gl_tex_sub_image_2d(self.width as i32, self.height as i32, self.format, self.ty, self.pixels.as_ptr() as *const std::ffi::c_void);
             self.texture.unbind();
         }
     }
 
+    pub(crate) fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+
+        if let RenderTextureResizing::ResizableLogical = self.resizing {
+            self.internal_resize(self.src_width, self.src_height);
+        }
+    }
+
     /// Resize the render texture, will clear the pixel buffer to 0.
     pub fn resize(&mut self, width: u32, height: u32) {
         self.src_width = width;
@@ -120,60 +127,140 @@ impl<T: RenderTextureType> RenderTexture<T> {
     fn internal_resize(&mut self, width: u32, height: u32) {
         self.src_width = width;
         self.src_height = height;
-        let (width, height) = Self::get_sized_dims(width, height, self.resizing);
+        let (width, height) = Self::get_sized_dims(width, height, self.scale_factor, self.resizing);
         self.width = width;
         self.height = height;
 
         gl_finish();
-        gl_pixel_store_i(gl::UNPACK_ALIGNMENT, 1);
 
-        let size = (width * height * std::mem::size_of::<T>() as u32 * 3) as usize;
+        self.pixels = vec![T::default(); (width * height * self.channels) as usize];
 
         self.texture = GLTexture::new(gl::TEXTURE_2D);
         self.pbo = GLPBO::new();
-        self.pixels = vec![T::default(); size / std::mem::size_of::<T>()];
+        self.setup_gl_resources();
+    }
 
+    /// Recreate the texture and PBO after the context lost them, the pixels are uploaded again on the next frame.
+    /// The stale objects are forgotten instead of dropped, their names may already belong to the new objects.
+    pub(crate) fn recreate_gl_resources(&mut self) {
+        std::mem::forget(std::mem::replace(&mut self.texture, GLTexture::new(gl::TEXTURE_2D)));
+        std::mem::forget(std::mem::replace(&mut self.pbo, GLPBO::new()));
+        self.setup_gl_resources();
+    }
+
+    /// Allocate the storage of the texture and PBO for the current size.
+    fn setup_gl_resources(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let size = std::mem::size_of_val(self.pixels.as_slice());
+
+        gl_pixel_store_i(gl::UNPACK_ALIGNMENT, 1);
         self.texture.bind(); {
             gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE);
             gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE);
             gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST);
             gl_tex_parami(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST);
 
-            gl_tex_image_2d(gl::RGB, width as i32, height as i32, gl::RGB, self.ty, std::ptr::null());
+            gl_tex_image_2d(self.internal_format, width as i32, height as i32, self.format, self.ty, std::ptr::null());
 
-            self.pbo.bind();
-            self.pbo.allocate(size);
-            self.pbo.unbind();
+            if self.use_pbo {
+                self.pbo.bind();
+                self.pbo.allocate(size);
+                self.pbo.unbind();
+            }
         } self.texture.unbind();
-
         gl_pixel_store_i(gl::UNPACK_ALIGNMENT, 4);
     }
 
-    fn get_sized_dims(width: u32, height: u32, resizing: RenderTextureResizing) -> (u32, u32) {
-        match resizing {
+    /// Get the render texture size for a window or viewport size, at least 1x1 like the window size it's based on.
+    fn get_sized_dims(width: u32, height: u32, scale_factor: f64, resizing: RenderTextureResizing) -> (u32, u32) {
+        let (width, height) = match resizing {
+            RenderTextureResizing::ResizableLogical => {
+                ((width as f64 / scale_factor) as u32, (height as f64 / scale_factor) as u32)
+            },
             RenderTextureResizing::ResizableScaled(scale) => {
                 ((width as f32 * scale) as u32, (height as f32 * scale) as u32)
             },
             _ => (width, height)
-        }
+        };
+        (width.max(1), height.max(1))
     }
 
     /// Get pixel at coordinates `[x, y]`.
     /// Always make sure `x >= 0 && x < width` AND `y >= 0 && y < height`.
     #[inline(always)]
     pub fn get_pixel(&self, x: u32, y: u32) -> (T, T, T) {
-        let i = ((y * self.width + x) * 3) as usize;
+        let i = ((y * self.width + x) * self.channels) as usize;
         (self.pixels[i], self.pixels[i + 1], self.pixels[i + 2])
     }
 
-    /// Set pixel at coordinates `[x, y]`.
+    /// Set pixel at coordinates `[x, y]`, the pixel is made opaque if the render texture has an alpha channel.
     /// Always make sure `x >= 0 && x < width` AND `y >= 0 && y < height`.
     #[inline(always)]
     pub fn set_pixel(&mut self, x: u32, y: u32, r: T, g: T, b: T) {
-        let i = ((y * self.width + x) * 3) as usize;
+        let i = ((y * self.width + x) * self.channels) as usize;
         self.pixels[i] = r;
         self.pixels[i + 1] = g;
         self.pixels[i + 2] = b;
+        if self.channels == 4 {
+            self.pixels[i + 3] = T::one();
+        }
+    }
+
+    /// Get pixel with alpha at coordinates `[x, y]`, the alpha is always one if the render texture has no alpha channel.
+    /// Always make sure `x >= 0 && x < width` AND `y >= 0 && y < height`.
+    #[inline(always)]
+    pub fn get_pixel_rgba(&self, x: u32, y: u32) -> (T, T, T, T) {
+        let i = ((y * self.width + x) * self.channels) as usize;
+        let a = if self.channels == 4 { self.pixels[i + 3] } else { T::one() };
+        (self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], a)
+    }
+
+    /// Set pixel with alpha at coordinates `[x, y]`, the alpha is ignored if the render texture has no alpha channel.
+    /// The color is not premultiplied by the alpha.
+    /// Always make sure `x >= 0 && x < width` AND `y >= 0 && y < height`.
+    #[inline(always)]
+    pub fn set_pixel_rgba(&mut self, x: u32, y: u32, r: T, g: T, b: T, a: T) {
+        let i = ((y * self.width + x) * self.channels) as usize;
+        self.pixels[i] = r;
+        self.pixels[i + 1] = g;
+        self.pixels[i + 2] = b;
+        if self.channels == 4 {
+            self.pixels[i + 3] = a;
+        }
+    }
+
+    /// Check if the render texture has an alpha channel.
+    pub fn has_alpha(&self) -> bool {
+        self.channels == 4
+    }
+
+    /// Get the pixels as 8-bit RGBA with the first row at the top, the same orientation as it is displayed.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity((self.width * self.height * 4) as usize);
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                let (r, g, b, a) = self.get_pixel_rgba(x, y);
+                rgba.extend_from_slice(&[
+                    (r.to_normalized() * 255.0).round() as u8,
+                    (g.to_normalized() * 255.0).round() as u8,
+                    (b.to_normalized() * 255.0).round() as u8,
+                    (a.to_normalized() * 255.0).round() as u8
+                ]);
+            }
+        }
+        rgba
+    }
+
+    /// Get the pixel that is displayed at a position in physical window pixels, `None` if the position is outside of the viewport or the render texture is empty.
+    /// Accounts for the viewport rectangle, the scaling of the render texture and its bottom left origin.
+    pub fn window_to_pixel(&self, window: &Window, position: Vector2<f32>) -> Option<Vector2<u32>> {
+        window_to_pixel(window.get_viewport_rect(), Vector2::new(self.width, self.height), position)
+    }
+
+    /// Get the position in physical window pixels of the center of a pixel, the inverse of `window_to_pixel`.
+    /// Returns `None` if the pixel is outside of the render texture.
+    pub fn pixel_to_window(&self, window: &Window, pixel: Vector2<u32>) -> Option<Vector2<f32>> {
+        pixel_to_window(window.get_viewport_rect(), Vector2::new(self.width, self.height), pixel)
     }
 
     /// Get width.
@@ -199,4 +286,108 @@ impl<T: RenderTextureType> RenderTexture<T> {
         self.resizing = resizing;
         self.internal_resize(self.src_width, self.src_height);
     }
-}
\ No newline at end of file
+}
+
+fn window_to_pixel((viewport_position, viewport_size): (Vector2<i32>, Vector2<u32>), size: Vector2<u32>, position: Vector2<f32>) -> Option<Vector2<u32>> {
+    if size.x == 0 || size.y == 0 {
+        return None;
+    }
+
+    let u = (position.x - viewport_position.x as f32) / viewport_size.x as f32;
+    let v = (position.y - viewport_position.y as f32) / viewport_size.y as f32;
+    if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+        return None;
+    }
+
+    let x = ((u * size.x as f32) as u32).min(size.x - 1);
+    let y = ((v * size.y as f32) as u32).min(size.y - 1);
+    Some(Vector2::new(x, size.y - 1 - y))
+}
+
+fn pixel_to_window((viewport_position, viewport_size): (Vector2<i32>, Vector2<u32>), size: Vector2<u32>, pixel: Vector2<u32>) -> Option<Vector2<f32>> {
+    if pixel.x >= size.x || pixel.y >= size.y {
+        return None;
+    }
+
+    let u = (pixel.x as f32 + 0.5) / size.x as f32;
+    let v = (size.y as f32 - pixel.y as f32 - 0.5) / size.y as f32;
+    Some(Vector2::new(
+        viewport_position.x as f32 + u * viewport_size.x as f32,
+        viewport_position.y as f32 + v * viewport_size.y as f32
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_to_pixel_flips_y() {
+        let viewport = (Vector2::new(0, 0), Vector2::new(4, 4));
+        let size = Vector2::new(4, 4);
+
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(0.0, 0.0)), Some(Vector2::new(0, 3)));
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(3.5, 3.5)), Some(Vector2::new(3, 0)));
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(4.0, 0.0)), None);
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(0.0, -0.5)), None);
+    }
+
+    #[test]
+    fn window_to_pixel_scaled_viewport() {
+        // A 2x2 render texture stretched over a 100x50 viewport at (10, 20).
+        let viewport = (Vector2::new(10, 20), Vector2::new(100, 50));
+        let size = Vector2::new(2, 2);
+
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(9.0, 30.0)), None);
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(10.0, 20.0)), Some(Vector2::new(0, 1)));
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(59.0, 44.0)), Some(Vector2::new(0, 1)));
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(60.0, 45.0)), Some(Vector2::new(1, 0)));
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(109.9, 69.9)), Some(Vector2::new(1, 0)));
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(110.0, 69.0)), None);
+        assert_eq!(window_to_pixel(viewport, size, Vector2::new(50.0, 70.0)), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let viewport = (Vector2::new(7, 3), Vector2::new(320, 180));
+        for size in [Vector2::new(320, 180), Vector2::new(96, 54), Vector2::new(1, 1), Vector2::new(640, 360)] {
+            for x in (0..size.x).step_by(5) {
+                for y in (0..size.y).step_by(3) {
+                    let pixel = Vector2::new(x, y);
+                    let position = pixel_to_window(viewport, size, pixel).unwrap();
+                    assert_eq!(window_to_pixel(viewport, size, position), Some(pixel));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn zero_size() {
+        let viewport = (Vector2::new(0, 0), Vector2::new(1, 1));
+        for size in [Vector2::new(0, 0), Vector2::new(0, 4), Vector2::new(4, 0)] {
+            assert_eq!(window_to_pixel(viewport, size, Vector2::new(0.5, 0.5)), None);
+            assert_eq!(pixel_to_window(viewport, size, Vector2::new(0, 0)), None);
+        }
+    }
+
+    #[test]
+    fn sized_dims_at_least_one() {
+        let sized_dims = RenderTexture::<u8>::get_sized_dims;
+        assert_eq!(sized_dims(1, 3, 2.0, RenderTextureResizing::ResizableLogical), (1, 1));
+        assert_eq!(sized_dims(1, 1, 3.0, RenderTextureResizing::ResizableLogical), (1, 1));
+        assert_eq!(sized_dims(5, 1, 1.0, RenderTextureResizing::ResizableScaled(0.1)), (1, 1));
+        assert_eq!(sized_dims(0, 0, 1.0, RenderTextureResizing::Resizable), (1, 1));
+        assert_eq!(sized_dims(300, 200, 2.0, RenderTextureResizing::ResizableLogical), (150, 100));
+        assert_eq!(sized_dims(300, 200, 2.0, RenderTextureResizing::ResizableScaled(0.5)), (150, 100));
+        assert_eq!(sized_dims(300, 200, 2.0, RenderTextureResizing::NonResizable), (300, 200));
+    }
+
+    #[test]
+    fn pixel_outside() {
+        let viewport = (Vector2::new(0, 0), Vector2::new(8, 8));
+        let size = Vector2::new(4, 4);
+        assert_eq!(pixel_to_window(viewport, size, Vector2::new(4, 0)), None);
+        assert_eq!(pixel_to_window(viewport, size, Vector2::new(0, 4)), None);
+        assert_eq!(pixel_to_window(viewport, size, Vector2::new(0, 3)), Some(Vector2::new(1.0, 1.0)));
+    }
+}
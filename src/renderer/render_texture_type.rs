@@ -1,11 +1,18 @@
+/// Element type of a `RenderTexture`, on OpenGL ES only `u8` and `f32` are supported.
 pub trait RenderTextureType: Copy + Default {
     fn get_type() -> u32;
+
+    /// Convert to a value in the range `[0, 1]` the same way OpenGL normalizes texture data.
+    fn to_normalized(self) -> f32;
+
+    /// The value OpenGL normalizes to one, used for opaque alpha.
+    fn one() -> Self;
 }
 
-impl RenderTextureType for u8 { fn get_type() -> u32 { gl::UNSIGNED_BYTE } }
-impl RenderTextureType for i8 { fn get_type() -> u32 { gl::BYTE } }
-impl RenderTextureType for u16 { fn get_type() -> u32 { gl::UNSIGNED_SHORT } }
-impl RenderTextureType for i16 { fn get_type() -> u32 { gl::SHORT } }
-impl RenderTextureType for u32 { fn get_type() -> u32 { gl::UNSIGNED_INT } }
-impl RenderTextureType for i32 { fn get_type() -> u32 { gl::INT } }
-impl RenderTextureType for f32 { fn get_type() -> u32 { gl::FLOAT } }
\ No newline at end of file
+impl RenderTextureType for u8 { fn get_type() -> u32 { gl::UNSIGNED_BYTE } fn to_normalized(self) -> f32 { self as f32 / u8::MAX as f32 } fn one() -> Self { u8::MAX } }
+impl RenderTextureType for i8 { fn get_type() -> u32 { gl::BYTE } fn to_normalized(self) -> f32 { (self as f32 / i8::MAX as f32).clamp(0.0, 1.0) } fn one() -> Self { i8::MAX } }
+impl RenderTextureType for u16 { fn get_type() -> u32 { gl::UNSIGNED_SHORT } fn to_normalized(self) -> f32 { self as f32 / u16::MAX as f32 } fn one() -> Self { u16::MAX } }
+impl RenderTextureType for i16 { fn get_type() -> u32 { gl::SHORT } fn to_normalized(self) -> f32 { (self as f32 / i16::MAX as f32).clamp(0.0, 1.0) } fn one() -> Self { i16::MAX } }
+impl RenderTextureType for u32 { fn get_type() -> u32 { gl::UNSIGNED_INT } fn to_normalized(self) -> f32 { self as f32 / u32::MAX as f32 } fn one() -> Self { u32::MAX } }
+impl RenderTextureType for i32 { fn get_type() -> u32 { gl::INT } fn to_normalized(self) -> f32 { (self as f32 / i32::MAX as f32).clamp(0.0, 1.0) } fn one() -> Self { i32::MAX } }
+impl RenderTextureType for f32 { fn get_type() -> u32 { gl::FLOAT } fn to_normalized(self) -> f32 { self.clamp(0.0, 1.0) } fn one() -> Self { 1.0 } }
\ No newline at end of file
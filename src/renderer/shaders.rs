@@ -1,21 +1,50 @@
+// Shader bodies are prefixed by `GLSLVersion::vertex_source`/`fragment_source` so they compile on every GL and GLES version.
+
 pub(crate) static DISPLAY_SHADER_SRC_VERT: &str = "
-#version 430 core
-out vec2 texcoords;
+VS_IN vec2 position;
+VS_OUT vec2 texcoords;
 
 void main() {
-    vec2 vertices[3]=vec2[3](vec2(-1,-1), vec2(3,-1), vec2(-1, 3));
-    gl_Position = vec4(vertices[gl_VertexID],0,1);
-    texcoords = 0.5 * gl_Position.xy + vec2(0.5);
+    gl_Position = vec4(position, 0.0, 1.0);
+    texcoords = 0.5 * position + vec2(0.5);
 }
 ";
 
 pub(crate) static DISPLAY_SHADER_SRC_FRAG: &str = "
-#version 430 core
 uniform sampler2D tex;
-in vec2 texcoords;
-out vec4 FragColor;
+FS_IN vec2 texcoords;
+
+void main() {
+    vec4 color = TEXTURE(tex, texcoords);
+    // Compositors expect premultiplied alpha, textures without alpha sample as opaque.
+    FRAG_COLOR = vec4(pow(color.rgb, vec3(2.2)) * color.a, color.a);
+}
+";
+
+pub(crate) static CURSOR_SHADER_SRC_VERT: &str = "
+uniform vec4 rect;
+VS_IN vec2 position;
+VS_OUT vec2 texcoords;
+
+void main() {
+    gl_Position = vec4(rect.xy + position * rect.zw, 0.0, 1.0);
+    texcoords = vec2(position.x, 1.0 - position.y);
+}
+";
+
+pub(crate) static CURSOR_SHADER_SRC_FRAG: &str = "
+uniform sampler2D tex;
+FS_IN vec2 texcoords;
 
 void main() {
-    FragColor.rgb = pow(texture(tex, texcoords).rgb, vec3(2.2));
+    FRAG_COLOR = TEXTURE(tex, texcoords);
 }
-";
\ No newline at end of file
+";
+
+/// A fullscreen triangle followed by a unit quad as a triangle strip.
+pub(crate) static QUAD_VERTICES: [f32; 14] = [
+    -1.0, -1.0,  3.0, -1.0,  -1.0, 3.0,
+    0.0, 0.0,  1.0, 0.0,  0.0, 1.0,  1.0, 1.0
+];
+pub(crate) const FULLSCREEN_TRIANGLE_FIRST: usize = 0;
+pub(crate) const UNIT_QUAD_FIRST: usize = 3;
@@ -0,0 +1,171 @@
+use cgmath::Vector2;
+
+/// A viewport length.
+/// - `Pixels(u32)` a length in physical pixels.
+/// - `Relative(f32)` a fraction of the window area that is left after the margins, `1.0` fills it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ViewportLength {
+    Pixels(u32),
+    Relative(f32)
+}
+
+/// The point of the window area the viewport is attached to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewportAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight
+}
+
+/// Space in physical pixels that is kept free at every edge of the window, e.g. for imgui side panels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ViewportMargin {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32
+}
+
+/// The rectangle of the window the render texture is displayed in, see `Window::set_viewport`.
+/// The render texture resizes to the viewport instead of the window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub width: ViewportLength,
+    pub height: ViewportLength,
+    pub anchor: ViewportAnchor,
+    pub margin: ViewportMargin
+}
+
+impl Default for Viewport {
+    /// The whole window.
+    fn default() -> Self {
+        Viewport {
+            width: ViewportLength::Relative(1.0),
+            height: ViewportLength::Relative(1.0),
+            anchor: ViewportAnchor::Center,
+            margin: ViewportMargin::default()
+        }
+    }
+}
+
+impl Viewport {
+    /// Get the position of the top left corner and the size of the viewport in a window of `window_size` physical pixels.
+    /// The size is at least 1x1 and never larger than the window area inside the margins.
+    /// Margins that leave no area collapse the viewport to 1x1 at the top left margin, kept inside the window.
+    pub fn rect(&self, window_size: Vector2<u32>) -> (Vector2<i32>, Vector2<u32>) {
+        let area_position = Vector2::new(
+            self.margin.left.min(window_size.x.saturating_sub(1)) as i32,
+            self.margin.top.min(window_size.y.saturating_sub(1)) as i32
+        );
+        let area_size = Vector2::new(
+            window_size.x.saturating_sub(self.margin.left.saturating_add(self.margin.right)),
+            window_size.y.saturating_sub(self.margin.top.saturating_add(self.margin.bottom))
+        );
+
+        let length = |length: ViewportLength, area: u32| {
+            let length = match length {
+                ViewportLength::Pixels(pixels) => pixels,
+                ViewportLength::Relative(fraction) => (area as f32 * fraction).round() as u32
+            };
+            length.min(area).max(1)
+        };
+        let size = Vector2::new(length(self.width, area_size.x), length(self.height, area_size.y));
+
+        // Fraction of the free space that lies before the viewport on each axis.
+        let (x, y) = match self.anchor {
+            ViewportAnchor::TopLeft => (0, 0),
+            ViewportAnchor::Top => (1, 0),
+            ViewportAnchor::TopRight => (2, 0),
+            ViewportAnchor::Left => (0, 1),
+            ViewportAnchor::Center => (1, 1),
+            ViewportAnchor::Right => (2, 1),
+            ViewportAnchor::BottomLeft => (0, 2),
+            ViewportAnchor::Bottom => (1, 2),
+            ViewportAnchor::BottomRight => (2, 2)
+        };
+        let free = Vector2::new(area_size.x.saturating_sub(size.x) as i32, area_size.y.saturating_sub(size.y) as i32);
+        let position = area_position + Vector2::new(free.x * x / 2, free.y * y / 2);
+
+        (position, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Vector2<u32> = Vector2::new(400, 300);
+
+    fn viewport(width: ViewportLength, height: ViewportLength, anchor: ViewportAnchor, margin: ViewportMargin) -> Viewport {
+        Viewport {
+            width,
+            height,
+            anchor,
+            margin
+        }
+    }
+
+    fn margin(left: u32, top: u32, right: u32, bottom: u32) -> ViewportMargin {
+        ViewportMargin {
+            left,
+            top,
+            right,
+            bottom
+        }
+    }
+
+    #[test]
+    fn whole_window() {
+        assert_eq!(Viewport::default().rect(WINDOW), (Vector2::new(0, 0), WINDOW));
+    }
+
+    #[test]
+    fn anchors() {
+        let rect = |anchor| viewport(ViewportLength::Pixels(100), ViewportLength::Pixels(50), anchor, ViewportMargin::default()).rect(WINDOW);
+        let size = Vector2::new(100, 50);
+
+        assert_eq!(rect(ViewportAnchor::TopLeft), (Vector2::new(0, 0), size));
+        assert_eq!(rect(ViewportAnchor::Top), (Vector2::new(150, 0), size));
+        assert_eq!(rect(ViewportAnchor::TopRight), (Vector2::new(300, 0), size));
+        assert_eq!(rect(ViewportAnchor::Left), (Vector2::new(0, 125), size));
+        assert_eq!(rect(ViewportAnchor::Center), (Vector2::new(150, 125), size));
+        assert_eq!(rect(ViewportAnchor::Right), (Vector2::new(300, 125), size));
+        assert_eq!(rect(ViewportAnchor::BottomLeft), (Vector2::new(0, 250), size));
+        assert_eq!(rect(ViewportAnchor::Bottom), (Vector2::new(150, 250), size));
+        assert_eq!(rect(ViewportAnchor::BottomRight), (Vector2::new(300, 250), size));
+    }
+
+    #[test]
+    fn margins() {
+        let margin = margin(10, 20, 30, 40);
+        let full = viewport(ViewportLength::Relative(1.0), ViewportLength::Relative(1.0), ViewportAnchor::Center, margin);
+        assert_eq!(full.rect(WINDOW), (Vector2::new(10, 20), Vector2::new(360, 240)));
+
+        let half = viewport(ViewportLength::Relative(0.5), ViewportLength::Relative(0.5), ViewportAnchor::BottomRight, margin);
+        assert_eq!(half.rect(WINDOW), (Vector2::new(190, 140), Vector2::new(180, 120)));
+    }
+
+    #[test]
+    fn margins_larger_than_window() {
+        let rect = |margin| viewport(ViewportLength::Relative(1.0), ViewportLength::Pixels(20), ViewportAnchor::Center, margin).rect(WINDOW);
+
+        assert_eq!(rect(margin(300, 0, 300, 0)), (Vector2::new(300, 140), Vector2::new(1, 20)));
+        assert_eq!(rect(margin(500, 500, 0, 0)), (Vector2::new(399, 299), Vector2::new(1, 1)));
+        assert_eq!(rect(margin(u32::MAX, u32::MAX, u32::MAX, u32::MAX)), (Vector2::new(399, 299), Vector2::new(1, 1)));
+    }
+
+    #[test]
+    fn pixels_larger_than_area() {
+        let large = viewport(ViewportLength::Pixels(1000), ViewportLength::Pixels(u32::MAX), ViewportAnchor::TopLeft, margin(0, 0, 100, 0));
+        assert_eq!(large.rect(WINDOW), (Vector2::new(0, 0), Vector2::new(300, 300)));
+
+        let empty = viewport(ViewportLength::Pixels(0), ViewportLength::Relative(-1.0), ViewportAnchor::TopLeft, ViewportMargin::default());
+        assert_eq!(empty.rect(WINDOW), (Vector2::new(0, 0), Vector2::new(1, 1)));
+    }
+}
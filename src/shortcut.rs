@@ -0,0 +1,36 @@
+pub use glutin::event::ModifiersState;
+
+use crate::{MouseButton, VirtualKeyCode};
+
+/// The key or mouse button that triggers a `Shortcut`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShortcutTrigger {
+    Key(VirtualKeyCode),
+    MouseButton(MouseButton)
+}
+
+/// A key or mouse button combined with an exact set of modifiers, e.g. Ctrl+S or Shift+click.
+/// See `Input::shortcut_pressed`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shortcut {
+    pub modifiers: ModifiersState,
+    pub trigger: ShortcutTrigger
+}
+
+impl Shortcut {
+    /// Create a keyboard shortcut, e.g. `Shortcut::key(ModifiersState::CTRL, VirtualKeyCode::S)`.
+    pub fn key(modifiers: ModifiersState, key_code: VirtualKeyCode) -> Self {
+        Shortcut {
+            modifiers,
+            trigger: ShortcutTrigger::Key(key_code)
+        }
+    }
+
+    /// Create a mouse shortcut, e.g. `Shortcut::mouse_button(ModifiersState::SHIFT, MouseButton::Left)`.
+    pub fn mouse_button(modifiers: ModifiersState, button: MouseButton) -> Self {
+        Shortcut {
+            modifiers,
+            trigger: ShortcutTrigger::MouseButton(button)
+        }
+    }
+}
@@ -1,98 +1,537 @@
+use std::path::{Path, PathBuf};
+
+use cgmath::Vector2;
+
 use crate::RcCell;
 use crate::CoreLoop;
+use crate::{Clipboard, ClipboardError};
+use crate::{RenderTexture, RenderTextureType, RenderTextureResizing, Viewport};
+use crate::WindowState;
+use crate::CursorImage;
+use crate::VirtualKeyCode;
+use crate::{Capture, CaptureTarget};
+use crate::{IconExt, ImageLoadError};
+use crate::gl_helpers::{gl_init, gl_info, gl_enable_debug_output, gl_swap_interval, GLCapabilities};
+use crate::{GlConfig, GlInfo};
+
+/// The presentation synchronization of the window.
+/// - `Off` frames are presented immediately, this may cause tearing.
+/// - `On` frames are presented on the vertical blank of the monitor.
+/// - `Adaptive` frames are presented on the vertical blank unless they are late, then they are presented immediately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapInterval {
+    Off,
+    On,
+    Adaptive
+}
 
-pub use glutin::window::{Icon, BadIcon, CursorIcon};
+/// How mouse events pass through the window to the windows behind it, see `Window::set_click_through`.
+/// - `Off` the window receives all mouse events.
+/// - `Window` mouse events pass through the whole window, for overlays that never take input.
+/// - `Transparent` mouse events pass through the pixels of a transparent window that are fully transparent after imgui and the cursor are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClickThrough {
+    Off,
+    Window,
+    Transparent
+}
+
+/// Window creation settings, see `minigw::new_with_config`.
+#[derive(Clone, Debug)]
+pub struct WindowConfig {
+    /// Window title.
+    pub title: String,
+    /// Inner width in logical pixels.
+    pub width: u32,
+    /// Inner height in logical pixels.
+    pub height: u32,
+    /// Request a vsynced context, defaults to `true`.
+    pub vsync: bool,
+    /// OpenGL context settings.
+    pub gl: GlConfig,
+    /// Create a window with a see-through background where the render texture alpha is zero, defaults to `false`.
+    /// The render textures of a transparent window have an alpha channel, see `RenderTexture::set_pixel_rgba`.
+    pub transparent: bool,
+    /// Persist the window geometry and render texture resizing mode between runs in a settings file keyed by this app name.
+    /// The name is used as a directory name, names with path separators or like `..` disable persistence. Defaults to `None` which disables persistence.
+    pub app_name: Option<String>
+}
+
+impl WindowConfig {
+    /// Create the default window settings with a title and inner size.
+    pub fn new(title: impl Into<String>, width: u32, height: u32) -> Self {
+        WindowConfig {
+            title: title.into(),
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            title: "minigw".to_owned(),
+            width: 1280,
+            height: 720,
+            vsync: true,
+            gl: GlConfig::default(),
+            transparent: false,
+            app_name: None
+        }
+    }
+}
+
+pub use glutin::window::{Icon, BadIcon, CursorIcon, Fullscreen};
+pub use glutin::monitor::{MonitorHandle, VideoMode};
+pub use glutin::error::ExternalError;
 
 /// Wrapper around a glutin window.
 pub struct Window {
-    context: glutin::ContextWrapper<glutin::PossiblyCurrent, glutin::window::Window>,
-    support_pbo: bool
+    // The context is split from the window so it can be rebuilt after a reset, it must be dropped first.
+    context: glutin::RawContext<glutin::PossiblyCurrent>,
+    window: glutin::window::Window,
+    gl_config: GlConfig,
+    context_lost: bool,
+    gl_info: GlInfo,
+    gl_capabilities: GLCapabilities,
+    srgb: bool,
+    clipboard: RcCell<Clipboard>,
+    title: String,
+    swap_interval: SwapInterval,
+    cursor_image: Option<CursorImage>,
+    cursor_image_generation: u64,
+    cursor_grabbed: bool,
+    app_name: Option<String>,
+    windowed_position: Option<Vector2<i32>>,
+    windowed_size: Vector2<u32>,
+    restored_resizing: Option<RenderTextureResizing>,
+    fullscreen_hotkeys: bool,
+    capture_request: Option<CaptureTarget>,
+    capture: Option<Capture>,
+    capture_hotkey: Option<VirtualKeyCode>,
+    scale_factor_changed: bool,
+    focused: bool,
+    transparent: bool,
+    click_through: ClickThrough,
+    cursor_hittest: bool,
+    viewport: Viewport
 }
 
 impl Window {
-    pub(crate) fn new(core_loop: &CoreLoop, title: &'static str, width: u32, height: u32) -> RcCell<Self> {        
-        let window_builder = glutin::window::WindowBuilder::new()
-            .with_title(title)
-            .with_inner_size(glutin::dpi::LogicalSize::new(width, height));
-
-        let mut support_pbo = true;
-        let context = glutin::ContextBuilder::new()
-            .with_gl(glutin::GlRequest::Specific(glutin::Api::OpenGl, (3, 0)))
+    pub(crate) fn new(core_loop: &CoreLoop, config: &WindowConfig) -> RcCell<Self> {
+        let mut window_builder = glutin::window::WindowBuilder::new()
+            .with_title(config.title.clone())
+            .with_inner_size(glutin::dpi::LogicalSize::new(config.width, config.height))
+            .with_transparent(config.transparent);
+
+        let restored_state = config.app_name.as_deref()
+            .and_then(WindowState::load)
+            .map(|mut state| {
+                let monitors: Vec<_> = core_loop.winit_loop().available_monitors()
+                    .map(|monitor| (Vector2::new(monitor.position().x, monitor.position().y), Vector2::new(monitor.size().width, monitor.size().height)))
+                    .collect();
+                state.clamp_to_monitors(&monitors);
+                state
+            });
+        if let Some(state) = &restored_state {
+            window_builder = window_builder
+                .with_inner_size(glutin::dpi::PhysicalSize::new(state.size.x, state.size.y))
+                .with_maximized(state.maximized);
+            if let Some(position) = state.position {
+                window_builder = window_builder.with_position(glutin::dpi::PhysicalPosition::new(position.x, position.y));
+            }
+            if state.fullscreen {
+                window_builder = window_builder.with_fullscreen(Some(Fullscreen::Borderless(None)));
+            }
+        }
+
+        let context = context_builder(&config.gl, config.vsync, config.gl.gl_request(), config.gl.gl_profile())
             .build_windowed(window_builder.clone(), core_loop.winit_loop())
             .unwrap_or_else(|_| -> _ {
-                support_pbo = false;
-                glutin::ContextBuilder::new()
-                    .with_gl(glutin::GlRequest::GlThenGles {
-                         opengl_version: (2, 0),
-                         opengles_version: (3, 0),
-                     })
-                    .build_windowed(window_builder, core_loop.winit_loop())
+                context_builder(&config.gl, config.vsync, FALLBACK_GL_REQUEST, None)
+                    .build_windowed(window_builder.clone(), core_loop.winit_loop())
                     .expect("Failed to create context.")
             });
 
-        let context = unsafe {
+        let (context, window) = unsafe {
             context.make_current()
                 .expect("Failed to make context current.")
+                .split()
         };
 
+        gl_init(&context);
+        let gl_info = gl_info(&context);
+        if config.gl.debug {
+            gl_enable_debug_output();
+        }
+
+        let windowed_position = restored_state.as_ref()
+            .and_then(|state| state.position)
+            .or_else(|| window.outer_position().ok().map(|position| Vector2::new(position.x, position.y)));
+        let windowed_size = restored_state.as_ref()
+            .map(|state| state.size)
+            .unwrap_or_else(|| Vector2::new(window.inner_size().width, window.inner_size().height));
+
+        let gl_capabilities = GLCapabilities::new(&gl_info);
         RcCell::new(Window {
             context,
-            support_pbo
+            window,
+            gl_config: config.gl,
+            context_lost: false,
+            gl_capabilities,
+            srgb: config.gl.srgb && gl_info.srgb && gl_capabilities.framebuffer_srgb,
+            gl_info,
+            clipboard: Clipboard::new(),
+            title: config.title.clone(),
+            swap_interval: if config.vsync { SwapInterval::On } else { SwapInterval::Off },
+            cursor_image: None,
+            cursor_image_generation: 0,
+            cursor_grabbed: false,
+            app_name: config.app_name.clone(),
+            windowed_position,
+            windowed_size,
+            restored_resizing: restored_state.and_then(|state| state.resizing),
+            fullscreen_hotkeys: false,
+            capture_request: None,
+            capture: None,
+            capture_hotkey: None,
+            scale_factor_changed: false,
+            focused: true,
+            transparent: config.transparent,
+            click_through: ClickThrough::Off,
+            cursor_hittest: true,
+            viewport: Viewport::default()
         })
     }
 
-    pub(crate) fn internal_context(&self) -> &glutin::ContextWrapper<glutin::PossiblyCurrent, glutin::window::Window> {
+    pub(crate) fn internal_context(&self) -> &glutin::RawContext<glutin::PossiblyCurrent> {
         &self.context
     }
 
     pub(crate) fn internal_window(&self) -> &glutin::window::Window {
-        self.context.window()
+        &self.window
     }
 
-    pub(crate) fn support_pbo(&self) -> bool {
-        self.support_pbo
+    /// Replace a lost context with a new one on the same window, returns `false` if the platform can't create it.
+    /// The new context may differ from the old one, so the GL info and capabilities are queried again.
+    pub(crate) fn rebuild_context(&mut self) -> bool {
+        let vsync = self.swap_interval != SwapInterval::Off;
+        let context = unsafe {
+            build_raw_context(context_builder(&self.gl_config, vsync, self.gl_config.gl_request(), self.gl_config.gl_profile()), &self.window)
+                .or_else(|| build_raw_context(context_builder(&self.gl_config, vsync, FALLBACK_GL_REQUEST, None), &self.window))
+                .and_then(|context| context.make_current().ok())
+        };
+        let context = match context {
+            Some(context) => context,
+            None => return false
+        };
+
+        self.context = context;
+        gl_init(&self.context);
+        self.gl_info = gl_info(&self.context);
+        self.gl_capabilities = GLCapabilities::new(&self.gl_info);
+        self.srgb = self.gl_config.srgb && self.gl_info.srgb && self.gl_capabilities.framebuffer_srgb;
+        if self.gl_config.debug {
+            gl_enable_debug_output();
+        }
+        if self.swap_interval == SwapInterval::Adaptive && !gl_swap_interval(self, -1) {
+            self.swap_interval = SwapInterval::On;
+        }
+
+        self.context_lost = false;
+        true
+    }
+
+    pub(crate) fn set_context_lost(&mut self) {
+        self.context_lost = true;
     }
 
-    /// Get inner with.
+    /// Check if the GL context was reset by the driver and has not been rebuilt, nothing is rendered while it is lost.
+    pub fn is_context_lost(&self) -> bool {
+        self.context_lost
+    }
+
+    pub(crate) fn gl_capabilities(&self) -> GLCapabilities {
+        self.gl_capabilities
+    }
+
+    pub(crate) fn srgb(&self) -> bool {
+        self.srgb
+    }
+
+    pub(crate) fn clipboard(&self) -> RcCell<Clipboard> {
+        self.clipboard.clone()
+    }
+
+    pub(crate) fn cursor_image(&self) -> Option<&CursorImage> {
+        self.cursor_image.as_ref()
+    }
+
+    pub(crate) fn cursor_image_generation(&self) -> u64 {
+        self.cursor_image_generation
+    }
+
+    pub(crate) fn set_cursor_grabbed(&mut self, grabbed: bool) {
+        self.cursor_grabbed = grabbed;
+        self.update_cursor_visible();
+    }
+
+    fn update_cursor_visible(&self) {
+        self.internal_window().set_cursor_visible(!self.cursor_grabbed && self.cursor_image.is_none());
+    }
+
+    pub(crate) fn restored_resizing(&self) -> Option<RenderTextureResizing> {
+        self.restored_resizing
+    }
+
+    /// Remember the geometry of the window while it is not maximized or fullscreen, so that is what gets persisted.
+    pub(crate) fn track_windowed_geometry(&mut self) {
+        if self.is_maximized() || self.is_fullscreen() {
+            return;
+        }
+
+        if let Some(position) = self.get_position() {
+            self.windowed_position = Some(position);
+        }
+        self.windowed_size = Vector2::new(self.get_physical_width(), self.get_physical_height());
+    }
+
+    pub(crate) fn save_state(&self, resizing: RenderTextureResizing) {
+        if let Some(app_name) = &self.app_name {
+            WindowState {
+                position: self.windowed_position,
+                size: self.windowed_size,
+                maximized: self.is_maximized(),
+                fullscreen: self.is_fullscreen(),
+                resizing: Some(resizing)
+            }.save(app_name);
+        }
+    }
+
+    pub(crate) fn update(&mut self) {
+        self.scale_factor_changed = false;
+    }
+
+    pub(crate) fn set_scale_factor_changed(&mut self) {
+        self.scale_factor_changed = true;
+    }
+
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// Get inner width in physical pixels, same as `get_physical_width()`.
     pub fn get_width(&self) -> u32 {
-        self.internal_window().inner_size().width
+        self.get_physical_width()
     }
 
-    /// Get inner height.
+    /// Get inner height in physical pixels, same as `get_physical_height()`.
     pub fn get_height(&self) -> u32 {
-        self.internal_window().inner_size().height
+        self.get_physical_height()
     }
 
-    /// Set inner width.
+    /// Set inner width in physical pixels, same as `set_physical_width(u32)`.
     pub fn set_width(&self, width: u32) {
+        self.set_physical_width(width);
+    }
+
+    /// Set inner height in physical pixels, same as `set_physical_height(u32)`.
+    pub fn set_height(&self, height: u32) {
+        self.set_physical_height(height);
+    }
+
+    /// Get inner width in physical pixels.
+    pub fn get_physical_width(&self) -> u32 {
+        self.internal_window().inner_size().width
+    }
+
+    /// Get inner height in physical pixels.
+    pub fn get_physical_height(&self) -> u32 {
+        self.internal_window().inner_size().height
+    }
+
+    /// Set inner width in physical pixels.
+    pub fn set_physical_width(&self, width: u32) {
+        self.internal_window().set_inner_size(
+            glutin::dpi::PhysicalSize::new(
+                width,
+                self.get_physical_height()
+            )
+        );
+    }
+
+    /// Set inner height in physical pixels.
+    pub fn set_physical_height(&self, height: u32) {
+        self.internal_window().set_inner_size(
+            glutin::dpi::PhysicalSize::new(
+                self.get_physical_width(),
+                height
+            )
+        );
+    }
+
+    /// Get inner width in logical pixels.
+    pub fn get_logical_width(&self) -> f64 {
+        self.get_physical_width() as f64 / self.get_scale_factor()
+    }
+
+    /// Get inner height in logical pixels.
+    pub fn get_logical_height(&self) -> f64 {
+        self.get_physical_height() as f64 / self.get_scale_factor()
+    }
+
+    /// Set inner width in logical pixels.
+    pub fn set_logical_width(&self, width: f64) {
         self.internal_window().set_inner_size(
             glutin::dpi::LogicalSize::new(
                 width,
-                self.internal_window().inner_size().height
+                self.get_logical_height()
             )
         );
     }
 
-    /// Set inner height.
-    pub fn set_height(&self, height: u32) {
+    /// Set inner height in logical pixels.
+    pub fn set_logical_height(&self, height: f64) {
         self.internal_window().set_inner_size(
             glutin::dpi::LogicalSize::new(
-                self.internal_window().inner_size().width,
+                self.get_logical_width(),
                 height
             )
         );
     }
 
+    /// Get the scale factor between logical and physical pixels, `physical = logical * scale_factor`.
+    pub fn get_scale_factor(&self) -> f64 {
+        self.internal_window().scale_factor()
+    }
+
+    /// Check if the scale factor changed since the previous frame.
+    /// This happens when the window moves to a monitor with a different DPI or when the DPI settings change.
+    pub fn scale_factor_changed(&self) -> bool {
+        self.scale_factor_changed
+    }
+
+    /// Get the OpenGL context configuration that was actually created, useful for diagnostics.
+    pub fn gl_info(&self) -> &GlInfo {
+        &self.gl_info
+    }
+
+    /// Get the window title.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Set the window title.
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+        self.internal_window().set_title(&self.title);
+    }
+
+    /// Get the text currently on the clipboard.
+    pub fn clipboard_text(&self) -> Result<String, ClipboardError> {
+        self.clipboard.as_mut().get_text()
+    }
+
+    /// Put text on the clipboard.
+    pub fn set_clipboard_text(&self, text: impl Into<String>) -> Result<(), ClipboardError> {
+        self.clipboard.as_mut().set_text(text.into())
+    }
+
+    /// Put the pixels of a render texture on the clipboard as an image.
+    pub fn set_clipboard_image<T: RenderTextureType>(&self, render_texture: &RenderTexture<T>) -> Result<(), ClipboardError> {
+        self.clipboard.as_mut().set_image(
+            render_texture.get_width(),
+            render_texture.get_height(),
+            render_texture.to_rgba8()
+        )
+    }
+
+    /// Get the current swap interval.
+    pub fn get_swap_interval(&self) -> SwapInterval {
+        self.swap_interval
+    }
+
+    /// Set the swap interval, returns `false` if the platform or driver doesn't support it (e.g. `SwapInterval::Adaptive` without `EXT_swap_control_tear`).
+    pub fn set_swap_interval(&mut self, swap_interval: SwapInterval) -> bool {
+        let interval = match swap_interval {
+            SwapInterval::Off => 0,
+            SwapInterval::On => 1,
+            SwapInterval::Adaptive => -1
+        };
+
+        let supported = gl_swap_interval(self, interval);
+        if supported {
+            self.swap_interval = swap_interval;
+        }
+        supported
+    }
+
     /// Set the window icon, if `icon == None` the os default window icon will be used.
     pub fn set_icon(&self, icon: Option<Icon>) {
         self.internal_window().set_window_icon(icon);
     }
 
+    /// Set the window icon from PNG, ICO or BMP data.
+    /// For a multi-resolution ICO the image that fits the window best is used, on Windows the taskbar gets its own best fitting image.
+    pub fn set_icon_from_bytes(&self, bytes: &[u8]) -> Result<(), ImageLoadError> {
+        #[cfg(target_os = "windows")]
+        {
+            use glutin::platform::windows::WindowExtWindows;
+
+            // Windows uses a 16x16 title bar icon and a 32x32 taskbar icon at 100% scaling.
+            let scale_factor = self.get_scale_factor();
+            self.set_icon(Some(Icon::from_bytes_sized(bytes, (16.0 * scale_factor).round() as u32)?));
+            self.internal_window().set_taskbar_icon(Some(Icon::from_bytes_sized(bytes, (32.0 * scale_factor).round() as u32)?));
+        }
+
+        // Other platforms use a single icon for both and scale it down themselves.
+        #[cfg(not(target_os = "windows"))]
+        self.set_icon(Some(Icon::from_bytes(bytes)?));
+
+        Ok(())
+    }
+
+    /// Set the window icon from a PNG, ICO or BMP file, see `set_icon_from_bytes(&[u8])`.
+    pub fn set_icon_from_path(&self, path: impl AsRef<Path>) -> Result<(), ImageLoadError> {
+        self.set_icon_from_bytes(&std::fs::read(path)?)
+    }
+
     /// Set the cursor icon.
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
         self.internal_window().set_cursor_icon(cursor);
     }
 
+    /// Set a custom cursor image, if `cursor == None` the cursor icon set with `set_cursor_icon(CursorIcon)` will be used.
+    /// The image is drawn on top of the window contents (including imgui) while the system cursor is hidden.
+    pub fn set_cursor_image(&mut self, cursor: Option<CursorImage>) {
+        self.cursor_image = cursor;
+        self.cursor_image_generation += 1;
+        self.update_cursor_visible();
+    }
+
+    /// Set if the input method editor (IME) is allowed, disabled by default.
+    /// While allowed, text composition is reported through `Input::ime_preedit()` and `Input::ime_commit()` instead of key presses.
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.internal_window().set_ime_allowed(allowed);
+    }
+
+    /// Set the position of the IME candidate window in window space, in physical pixels.
+    /// While an imgui text field is focused the candidate window is placed at its text cursor automatically.
+    pub fn set_ime_position(&self, position: Vector2<i32>) {
+        self.internal_window().set_ime_position(
+            glutin::dpi::PhysicalPosition::new(position.x, position.y)
+        );
+    }
+
+    /// Move the cursor to a position in window space, in physical pixels.
+    pub fn set_cursor_position(&self, position: Vector2<i32>) -> Result<(), ExternalError> {
+        self.internal_window().set_cursor_position(
+            glutin::dpi::PhysicalPosition::new(position.x, position.y)
+        )
+    }
+
     /// Get if the window is resizable.
     pub fn is_resizable(&self) -> bool {
         self.internal_window().is_resizable()
@@ -102,4 +541,352 @@ impl Window {
     pub fn set_resizable(&self, resizable: bool) {
         self.internal_window().set_resizable(resizable);
     }
-}
\ No newline at end of file
+
+    /// Get the position of the top-left corner of the window including decorations, in physical pixels.
+    /// Returns `None` if the platform does not support this (Wayland).
+    pub fn get_position(&self) -> Option<Vector2<i32>> {
+        self.internal_window().outer_position()
+            .ok()
+            .map(|position| Vector2::new(position.x, position.y))
+    }
+
+    /// Set the position of the top-left corner of the window including decorations, in physical pixels.
+    pub fn set_position(&self, position: Vector2<i32>) {
+        self.internal_window().set_outer_position(
+            glutin::dpi::PhysicalPosition::new(position.x, position.y)
+        );
+    }
+
+    /// Set the minimum inner size in physical pixels, if `size == None` there will be no minimum.
+    pub fn set_min_size(&self, size: Option<Vector2<u32>>) {
+        self.internal_window().set_min_inner_size(
+            size.map(|size| glutin::dpi::PhysicalSize::new(size.x, size.y))
+        );
+    }
+
+    /// Set the maximum inner size in physical pixels, if `size == None` there will be no maximum.
+    pub fn set_max_size(&self, size: Option<Vector2<u32>>) {
+        self.internal_window().set_max_inner_size(
+            size.map(|size| glutin::dpi::PhysicalSize::new(size.x, size.y))
+        );
+    }
+
+    /// Get if the window has decorations (title bar and borders).
+    pub fn is_decorated(&self) -> bool {
+        self.internal_window().is_decorated()
+    }
+
+    /// Set if the window has decorations (title bar and borders).
+    pub fn set_decorations(&self, decorations: bool) {
+        self.internal_window().set_decorations(decorations);
+    }
+
+    /// Set if the window is always on top of other windows.
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        self.internal_window().set_always_on_top(always_on_top);
+    }
+
+    /// Get if the window is maximized.
+    pub fn is_maximized(&self) -> bool {
+        self.internal_window().is_maximized()
+    }
+
+    /// Set if the window is maximized.
+    pub fn set_maximized(&self, maximized: bool) {
+        self.internal_window().set_maximized(maximized);
+    }
+
+    /// Set if the window is minimized.
+    pub fn set_minimized(&self, minimized: bool) {
+        self.internal_window().set_minimized(minimized);
+    }
+
+    /// Get if the window is visible.
+    /// Returns `None` if the platform does not support this (Wayland, Android, Web).
+    pub fn is_visible(&self) -> Option<bool> {
+        self.internal_window().is_visible()
+    }
+
+    /// Set if the window is visible.
+    pub fn set_visible(&self, visible: bool) {
+        self.internal_window().set_visible(visible);
+    }
+
+    /// Get if the window has keyboard focus.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Get the rectangle of the window the render texture is displayed in.
+    pub fn get_viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    /// Set the rectangle of the window the render texture is displayed in, defaults to the whole window.
+    /// The render texture resizes to the viewport instead of the window, the change is applied at the start of the next frame.
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+    }
+
+    /// Get the position of the top left corner and the size of the viewport in physical pixels.
+    pub fn get_viewport_rect(&self) -> (Vector2<i32>, Vector2<u32>) {
+        self.viewport.rect(Vector2::new(self.get_physical_width().max(1), self.get_physical_height().max(1)))
+    }
+
+    /// Check if the window was created with a transparent background.
+    pub fn is_transparent(&self) -> bool {
+        self.transparent
+    }
+
+    /// Get how mouse events pass through the window.
+    pub fn get_click_through(&self) -> ClickThrough {
+        self.click_through
+    }
+
+    /// Set how mouse events pass through the window to the windows behind it, defaults to `ClickThrough::Off`.
+    /// `ClickThrough::Transparent` reads back the pixel under the cursor every frame and is only supported for transparent windows on Windows and macOS,
+    /// other platforms can't report the cursor position while mouse events pass through. `ClickThrough::Window` isn't supported on X11, iOS, Android and Web.
+    /// Returns `false` and keeps the current mode if the mode isn't supported.
+    pub fn set_click_through(&mut self, click_through: ClickThrough) -> bool {
+        let hittest = match click_through {
+            ClickThrough::Off | ClickThrough::Transparent => true,
+            ClickThrough::Window => false
+        };
+        if click_through == ClickThrough::Transparent && (!self.transparent || screen_cursor_position(self.get_scale_factor()).is_none()) {
+            return false;
+        }
+        // X11 has no hittest at all, so turning it back on only fails where it was never off.
+        if self.internal_window().set_cursor_hittest(hittest).is_err() && click_through == ClickThrough::Window {
+            return false;
+        }
+
+        self.click_through = click_through;
+        self.cursor_hittest = hittest;
+        true
+    }
+
+    /// Get the cursor position in physical pixels while `ClickThrough::Transparent` needs to know if the pixel under it is opaque.
+    pub(crate) fn click_through_cursor_position(&self) -> Option<Vector2<i32>> {
+        if self.click_through != ClickThrough::Transparent {
+            return None;
+        }
+
+        let cursor = screen_cursor_position(self.get_scale_factor())?;
+        let origin = self.internal_window().inner_position().ok()?;
+        Some(Vector2::new(cursor.x - origin.x, cursor.y - origin.y))
+    }
+
+    /// Let mouse events through when the pixel under the cursor is fully transparent, see `ClickThrough::Transparent`.
+    pub(crate) fn set_cursor_opaque(&mut self, opaque: bool) {
+        if self.click_through == ClickThrough::Transparent && self.cursor_hittest != opaque && self.internal_window().set_cursor_hittest(opaque).is_ok() {
+            self.cursor_hittest = opaque;
+        }
+    }
+
+    /// Get all monitors connected to the system.
+    pub fn monitors(&self) -> Vec<MonitorHandle> {
+        self.internal_window().available_monitors().collect()
+    }
+
+    /// Get the monitor the window currently resides on.
+    pub fn current_monitor(&self) -> Option<MonitorHandle> {
+        self.internal_window().current_monitor()
+    }
+
+    /// Get the primary monitor of the system.
+    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
+        self.internal_window().primary_monitor()
+    }
+
+    /// Get the current fullscreen mode, `None` if the window is windowed.
+    pub fn get_fullscreen(&self) -> Option<Fullscreen> {
+        self.internal_window().fullscreen()
+    }
+
+    /// Set the fullscreen mode, if `fullscreen == None` the window will be windowed.
+    /// - `Fullscreen::Borderless(Option<MonitorHandle>)` borderless fullscreen on the given monitor, `None` uses the current monitor.
+    /// - `Fullscreen::Exclusive(VideoMode)` exclusive fullscreen with a video mode from `MonitorHandle::video_modes()`.
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        self.internal_window().set_fullscreen(fullscreen);
+    }
+
+    /// Check if the window is fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        self.get_fullscreen().is_some()
+    }
+
+    /// Toggle between windowed and borderless fullscreen on the current monitor.
+    pub fn toggle_fullscreen(&self) {
+        if self.is_fullscreen() {
+            self.set_fullscreen(None);
+        } else {
+            self.set_fullscreen(Some(Fullscreen::Borderless(self.current_monitor())));
+        }
+    }
+
+    /// Get if Alt+Enter and F11 toggle fullscreen.
+    pub fn get_fullscreen_hotkeys(&self) -> bool {
+        self.fullscreen_hotkeys
+    }
+
+    /// Set if Alt+Enter and F11 toggle fullscreen, disabled by default.
+    pub fn set_fullscreen_hotkeys(&mut self, enabled: bool) {
+        self.fullscreen_hotkeys = enabled;
+    }
+
+    /// Capture the window as it is presented at the end of this frame, including imgui and the cursor image.
+    /// The pixels are read back asynchronously, the capture is available from `take_capture()` on one of the next frames.
+    pub fn capture(&mut self) {
+        self.capture_request = Some(CaptureTarget::Memory);
+    }
+
+    /// Capture the window as it is presented at the end of this frame and save it as a PNG file.
+    /// The file is encoded and written on a background thread, see `Capture::save_png`.
+    pub fn capture_to_path(&mut self, path: impl Into<PathBuf>) {
+        self.capture_request = Some(CaptureTarget::Path(path.into()));
+    }
+
+    /// Take the most recent capture requested with `capture()`, `None` if it isn't finished yet.
+    pub fn take_capture(&mut self) -> Option<Capture> {
+        self.capture.take()
+    }
+
+    /// Get the key that saves a capture to the working directory.
+    pub fn get_capture_hotkey(&self) -> Option<VirtualKeyCode> {
+        self.capture_hotkey
+    }
+
+    /// Set a key that saves a capture to `minigw_capture_<timestamp>.png` in the working directory, disabled by default.
+    pub fn set_capture_hotkey(&mut self, key: Option<VirtualKeyCode>) {
+        self.capture_hotkey = key;
+    }
+
+    pub(crate) fn take_capture_request(&mut self) -> Option<CaptureTarget> {
+        self.capture_request.take()
+    }
+
+    pub(crate) fn set_capture(&mut self, capture: Capture) {
+        self.capture = Some(capture);
+    }
+}
+/// Used when the requested context can't be created.
+const FALLBACK_GL_REQUEST: glutin::GlRequest = glutin::GlRequest::GlThenGles {
+    opengl_version: (2, 0),
+    opengles_version: (3, 0)
+};
+
+/// Get the cursor position on the screen in physical pixels, even while the cursor is over another window.
+#[cfg(target_os = "windows")]
+fn screen_cursor_position(_scale_factor: f64) -> Option<Vector2<i32>> {
+    #[repr(C)]
+    struct Point {
+        x: i32,
+        y: i32
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetCursorPos(point: *mut Point) -> i32;
+    }
+
+    let mut point = Point { x: 0, y: 0 };
+    // winit makes the process DPI aware, so this is in physical pixels.
+    (unsafe { GetCursorPos(&mut point) } != 0).then(|| Vector2::new(point.x, point.y))
+}
+
+/// Get the cursor position on the screen in physical pixels, even while the cursor is over another window.
+#[cfg(target_os = "macos")]
+fn screen_cursor_position(scale_factor: f64) -> Option<Vector2<i32>> {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    struct CGPoint {
+        x: f64,
+        y: f64
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventCreate(source: *const c_void) -> *const c_void;
+        fn CGEventGetLocation(event: *const c_void) -> CGPoint;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(object: *const c_void);
+    }
+
+    unsafe {
+        let event = CGEventCreate(std::ptr::null());
+        if event.is_null() {
+            return None;
+        }
+        // Global coordinates in points from the top left of the main display, like the window position winit reports.
+        let location = CGEventGetLocation(event);
+        CFRelease(event);
+        Some(Vector2::new((location.x * scale_factor) as i32, (location.y * scale_factor) as i32))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn screen_cursor_position(_scale_factor: f64) -> Option<Vector2<i32>> {
+    None
+}
+
+fn context_builder<'a>(gl: &GlConfig, vsync: bool, gl_request: glutin::GlRequest, gl_profile: Option<glutin::GlProfile>) -> glutin::ContextBuilder<'a, glutin::NotCurrent> {
+    let mut context_builder = glutin::ContextBuilder::new()
+        .with_gl(gl_request)
+        .with_vsync(vsync)
+        .with_gl_debug_flag(gl.debug)
+        .with_gl_robustness(glutin::Robustness::TryRobustLoseContextOnReset)
+        .with_srgb(gl.srgb)
+        .with_multisampling(gl.samples)
+        .with_depth_buffer(gl.depth_bits)
+        .with_stencil_buffer(gl.stencil_bits);
+    if let Some(gl_profile) = gl_profile {
+        context_builder = context_builder.with_gl_profile(gl_profile);
+    }
+    context_builder
+}
+
+/// Create a context for an existing window.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+unsafe fn build_raw_context(context_builder: glutin::ContextBuilder<glutin::NotCurrent>, window: &glutin::window::Window) -> Option<glutin::RawContext<glutin::NotCurrent>> {
+    use glutin::platform::unix::{RawContextExt, WindowExtUnix};
+
+    if let (Some(xconn), Some(xwin)) = (window.xlib_xconnection(), window.xlib_window()) {
+        return context_builder.build_raw_x11_context(xconn, xwin).ok();
+    }
+    if let (Some(display), Some(surface)) = (window.wayland_display(), window.wayland_surface()) {
+        let size = window.inner_size();
+        return context_builder.build_raw_wayland_context(display as *const _, surface, size.width, size.height).ok();
+    }
+    None
+}
+
+/// Create a context for an existing window.
+#[cfg(target_os = "windows")]
+unsafe fn build_raw_context(context_builder: glutin::ContextBuilder<glutin::NotCurrent>, window: &glutin::window::Window) -> Option<glutin::RawContext<glutin::NotCurrent>> {
+    use glutin::platform::windows::{RawContextExt, WindowExtWindows};
+
+    context_builder.build_raw_context(window.hwnd() as isize).ok()
+}
+
+/// Create a context for an existing window, not supported on this platform.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows",
+)))]
+unsafe fn build_raw_context(_context_builder: glutin::ContextBuilder<glutin::NotCurrent>, _window: &glutin::window::Window) -> Option<glutin::RawContext<glutin::NotCurrent>> {
+    None
+}